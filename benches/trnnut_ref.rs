@@ -0,0 +1,50 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Ref benchmarks
+//!
+//! Validating a call against an encoded TRNNut, by decoding it into a
+//! `TRNNutV0` or by viewing it as a `TRNNutRef`
+//!
+
+use codec::{Decode, Encode};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use pact::types::{Numeric, PactType};
+use trnnut_rs::{constraint::arg, method::Method, module::Module, TRNNutRef, TRNNutV0};
+
+fn validate(c: &mut Criterion) {
+    let args = [PactType::Numeric(Numeric(100))];
+    let mut group = c.benchmark_group("validate_module");
+    for modules in [1, 16, 64] {
+        // Each module has 16 methods and then `transfer`, whose constraints are validated
+        let encoded = TRNNutV0 {
+            modules: (0..modules)
+                .map(|i| {
+                    let methods = (0..16)
+                        .map(|j| Method::new(&format!("method_{j}")).block_cooldown(10))
+                        .chain([Method::new("transfer").constrain(arg(0).le(1000)).unwrap()])
+                        .collect();
+                    Module::new(&format!("module_{i}")).methods(methods)
+                })
+                .collect(),
+        }
+        .encode();
+        let module = format!("module_{}", modules - 1);
+
+        group.bench_with_input(BenchmarkId::new("TRNNutV0", modules), &encoded, |b, e| {
+            b.iter(|| {
+                let trnnut = TRNNutV0::decode(&mut &black_box(e)[..]).unwrap();
+                trnnut.validate_module(&module, "transfer", &args)
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("TRNNutRef", modules), &encoded, |b, e| {
+            b.iter(|| {
+                let trnnut = TRNNutRef::new(black_box(e)).unwrap();
+                trnnut.validate_module(&module, "transfer", &args)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, validate);
+criterion_main!(benches);
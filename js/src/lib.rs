@@ -5,59 +5,590 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
-use alloc::{format, vec::Vec};
-use codec::{Decode, Encode};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use codec::Decode;
+use core::convert::TryFrom;
+use js_sys::{Array, Reflect, Uint8Array};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 use trnnut_rs::{
-    method::Method, module::Module, TRNNutV0,
+    constraint::{CompareOp, Comparison, Constraint, ConstraintError, Operand},
+    json::PactValueJson,
+    method::Method,
+    module::Module,
+    pact::types::{Contract as PactContract, Numeric, PactType, StringLike},
+    EditError, EncodeError, MethodName, ModuleName, RuntimeDomain, TRNNutV0, TryEncode,
+    ValidationErr,
 };
-use wasm_bindgen::prelude::*;
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-}
-
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
-}
+use wasm_bindgen::{prelude::*, JsCast};
 
 #[wasm_bindgen(typescript_custom_section)]
-const MODULE_METHOD_TYPE_DEFINITION: &str = r#"
+const METHOD_CONFIG_TYPE_DEFINITION: &str = r#"
 /** Method configuration for modules. */
-export type ModuleMethod = {
+export type MethodConfig = {
     /** Method name */
     readonly name: string;
     /** Block cooldown */
     readonly blockCooldown?: number;
-    /** Constraints */
-    readonly constraints?: Uint8Array;
+    /** Constraints, read as an array of bytes */
+    readonly constraints?: Uint8Array | ReadonlyArray<number>;
+    /** Deny the method instead of granting it */
+    readonly deny?: boolean;
 };
 "#;
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(typescript_type = "ModuleMethod")]
-    pub type ModuleMethodJS;
+    #[wasm_bindgen(typescript_type = "MethodConfig")]
+    pub type MethodConfigJS;
 }
 
 #[wasm_bindgen(typescript_custom_section)]
-const MODULE_TYPE_DEFINITION: &str = r#"
+const MODULE_CONFIG_TYPE_DEFINITION: &str = r#"
 /** Module configuration.  */
-export type Module = {
+export type ModuleConfig = {
     /** Module name */
     readonly name: string;
     /** Block cooldown */
     readonly blockCooldown?: number;
     /** Methods - i.e. module extrinsics */
-    readonly methods: ReadonlyArray<ModuleMethod>;
+    readonly methods: ReadonlyArray<MethodConfig>;
+    /** Deny every method of the module instead of granting them */
+    readonly deny?: boolean;
 };
 "#;
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(typescript_type = "Module")]
-    pub type ModuleJS;
+    #[wasm_bindgen(typescript_type = "ModuleConfig")]
+    pub type ModuleConfigJS;
+
+    #[wasm_bindgen(typescript_type = "ReadonlyArray<ModuleConfig>")]
+    pub type ModuleConfigsJS;
+}
+
+/// A `MethodConfig`, mapping the camelCase JS fields onto `Method`
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MethodConfig {
+    #[serde(deserialize_with = "deserialize_method_name")]
+    name: String,
+    // `block_cooldown` is still read, as it was the field name before
+    #[serde(
+        default,
+        alias = "block_cooldown",
+        skip_serializing_if = "Option::is_none"
+    )]
+    block_cooldown: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    constraints: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    deny: bool,
+}
+
+/// A `ModuleConfig`, mapping the camelCase JS fields onto `Module`
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModuleConfig {
+    #[serde(deserialize_with = "deserialize_module_name")]
+    name: String,
+    // `block_cooldown` is still read, as it was the field name before
+    #[serde(
+        default,
+        alias = "block_cooldown",
+        skip_serializing_if = "Option::is_none"
+    )]
+    block_cooldown: Option<u32>,
+    methods: Vec<MethodConfig>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    deny: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Rejects module names which would not encode intact
+fn deserialize_module_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    ModuleName::deserialize(deserializer).map(String::from)
+}
+
+/// Rejects method names which would not encode intact
+fn deserialize_method_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    MethodName::deserialize(deserializer).map(String::from)
+}
+
+impl From<MethodConfig> for Method {
+    fn from(config: MethodConfig) -> Self {
+        Method {
+            name: config.name,
+            block_cooldown: config.block_cooldown,
+            constraints: config.constraints,
+            deny: config.deny,
+        }
+    }
+}
+
+impl From<&Method> for MethodConfig {
+    fn from(method: &Method) -> Self {
+        MethodConfig {
+            name: method.name.clone(),
+            block_cooldown: method.block_cooldown,
+            constraints: method.constraints.clone(),
+            deny: method.deny,
+        }
+    }
+}
+
+impl From<ModuleConfig> for Module {
+    fn from(config: ModuleConfig) -> Self {
+        Module {
+            name: config.name,
+            block_cooldown: config.block_cooldown,
+            methods: config.methods.into_iter().map(Method::from).collect(),
+            deny: config.deny,
+        }
+    }
+}
+
+impl From<&Module> for ModuleConfig {
+    fn from(module: &Module) -> Self {
+        ModuleConfig {
+            name: module.name.clone(),
+            block_cooldown: module.block_cooldown,
+            methods: module.methods.iter().map(MethodConfig::from).collect(),
+            deny: module.deny,
+        }
+    }
+}
+
+/// Read a JS object with serde, failing with a `TRNNutError` of `code`
+fn from_js<T: DeserializeOwned>(
+    value: JsValue,
+    code: &'static str,
+    what: &str,
+) -> Result<T, TRNNutError> {
+    serde_wasm_bindgen::from_value(value)
+        .map_err(|err| TRNNutError::new(code, format!("invalid {what}: {err}")))
+}
+
+/// Write `value` as a JS object of the extern type `T`
+fn to_js<T: JsCast>(value: &impl serde::Serialize) -> Result<T, JsValue> {
+    let serializer =
+        serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    serde::Serialize::serialize(value, &serializer)
+        .map(JsCast::unchecked_into)
+        .map_err(Into::into)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const ERROR_TYPE_DEFINITION: &str = r#"
+/** What went wrong in a TRNNut call */
+export type TRNNutErrorCode =
+    | "DECODE"
+    | "INVALID_MODULES"
+    | "INVALID_NAME"
+    | "INVALID_ARGUMENT"
+    | "INVALID_CONSTRAINTS"
+    | "NO_MODULES"
+    | "TOO_MANY_MODULES"
+    | "NO_METHODS"
+    | "TOO_MANY_METHODS"
+    | "NAME_TOO_LONG"
+    | "CONSTRAINTS_TOO_LONG"
+    | "PAYLOAD_TOO_LARGE"
+    | "MODULE_DENY_UNSUPPORTED"
+    | "DUPLICATE_MODULE"
+    | "DUPLICATE_METHOD"
+    | "NO_SUCH_MODULE"
+    | "NO_SUCH_METHOD";
+
+/** The error thrown by TRNNut calls */
+export interface TRNNutError extends Error {
+    readonly name: "TRNNutError";
+    readonly code: TRNNutErrorCode;
+}
+"#;
+
+/// An error thrown to JS as a `TRNNutError`, see `TRNNutErrorCode` for the codes
+struct TRNNutError {
+    code: &'static str,
+    message: String,
+}
+
+impl TRNNutError {
+    fn new(code: &'static str, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<EncodeError> for TRNNutError {
+    fn from(err: EncodeError) -> Self {
+        let code = match err {
+            EncodeError::NoModules => "NO_MODULES",
+            EncodeError::TooManyModules => "TOO_MANY_MODULES",
+            EncodeError::NoMethods { .. } => "NO_METHODS",
+            EncodeError::TooManyMethods { .. } => "TOO_MANY_METHODS",
+            EncodeError::NameTooLong => "NAME_TOO_LONG",
+            EncodeError::NameContainsNul => "INVALID_NAME",
+            EncodeError::ConstraintsTooLong => "CONSTRAINTS_TOO_LONG",
+            EncodeError::PayloadTooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            EncodeError::ModuleDenyUnsupported { .. } => "MODULE_DENY_UNSUPPORTED",
+            EncodeError::MethodDenyUnsupported { .. } => "METHOD_DENY_UNSUPPORTED",
+        };
+        Self::new(code, err)
+    }
+}
+
+impl From<EditError> for TRNNutError {
+    fn from(err: EditError) -> Self {
+        let code = match err {
+            EditError::DuplicateModule { .. } => "DUPLICATE_MODULE",
+            EditError::DuplicateMethod { .. } => "DUPLICATE_METHOD",
+            EditError::NoSuchModule { .. } => "NO_SUCH_MODULE",
+            EditError::NoSuchMethod { .. } => "NO_SUCH_METHOD",
+            EditError::InvalidName { .. } => "INVALID_NAME",
+            EditError::InvalidConstraints { .. } => "INVALID_CONSTRAINTS",
+            EditError::ConstraintsTooLong { .. } => "CONSTRAINTS_TOO_LONG",
+            EditError::NoModules => "NO_MODULES",
+            EditError::TooManyModules => "TOO_MANY_MODULES",
+            EditError::NoMethods { .. } => "NO_METHODS",
+            EditError::TooManyMethods { .. } => "TOO_MANY_METHODS",
+            EditError::PayloadTooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            EditError::ModuleDenyUnsupported { .. } => "MODULE_DENY_UNSUPPORTED",
+            EditError::MethodDenyUnsupported { .. } => "METHOD_DENY_UNSUPPORTED",
+        };
+        Self::new(code, err)
+    }
+}
+
+impl From<TRNNutError> for JsValue {
+    fn from(err: TRNNutError) -> Self {
+        let error = js_sys::Error::new(&err.message);
+        error.set_name("TRNNutError");
+        // Setting a property of a plain `Error` object can't fail
+        let _ = Reflect::set(&error, &"code".into(), &err.code.into());
+        error.into()
+    }
+}
+
+/// Check that the constraints of `method` of `module` are a Pact contract, as decoding requires
+fn check_constraints(module: Option<&str>, method: &Method) -> Result<(), TRNNutError> {
+    if method.constraints.is_some() && method.get_pact().is_none() {
+        let method = &method.name;
+        let message = match module {
+            Some(module) => format!(
+                "constraints of method {method} of module {module} are not a valid Pact contract"
+            ),
+            None => format!("constraints of method {method} are not a valid Pact contract"),
+        };
+        return Err(TRNNutError::new("INVALID_CONSTRAINTS", message));
+    }
+    Ok(())
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const VALIDATION_TYPE_DEFINITION: &str = r#"
+/** A method argument, numbers are unsigned integers and strings or bytes are string-like */
+export type ValidationArg = number | bigint | string | Uint8Array | ReadonlyArray<ValidationArg>;
+
+/** The outcome of validating a method call */
+export type ValidationResult =
+    | { readonly ok: true }
+    | {
+        readonly ok: false;
+        /** The domain not granted, absent when the constraints could not be interpreted */
+        readonly domain?: "module" | "method" | "methodArguments";
+        /** Why the call is not permitted */
+        readonly reason: string;
+    };
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ReadonlyArray<ValidationArg>")]
+    pub type ValidationArgsJS;
+
+    #[wasm_bindgen(typescript_type = "ValidationResult")]
+    pub type ValidationResultJS;
+}
+
+/// The outcome of `TRNNutJS::validate`, see `ValidationResult`
+#[derive(serde::Serialize)]
+struct ValidationResult {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl From<Result<(), ValidationErr<RuntimeDomain>>> for ValidationResult {
+    fn from(result: Result<(), ValidationErr<RuntimeDomain>>) -> Self {
+        let Err(err) = result else {
+            return Self {
+                ok: true,
+                domain: None,
+                reason: None,
+            };
+        };
+        let domain = match &err {
+            ValidationErr::NoPermission(RuntimeDomain::Module) => Some("module"),
+            ValidationErr::NoPermission(RuntimeDomain::Method) => Some("method"),
+            ValidationErr::NoPermission(RuntimeDomain::MethodArguments) => Some("methodArguments"),
+            _ => None,
+        };
+        Self {
+            ok: false,
+            domain,
+            reason: Some(err.to_string()),
+        }
+    }
+}
+
+/// Convert a JS method argument into a Pact value
+fn to_pact_arg(value: &JsValue) -> Result<PactType, TRNNutError> {
+    // Integers above this lose precision as JS numbers, and should be passed as bigints
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+    if let Some(string) = value.as_string() {
+        return Ok(PactType::StringLike(StringLike(string.into_bytes())));
+    }
+    if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+        return Ok(PactType::StringLike(StringLike(bytes.to_vec())));
+    }
+    if let Some(number) = value.as_f64() {
+        if number >= 0.0 && number.fract() == 0.0 && number <= MAX_SAFE_INTEGER {
+            return Ok(PactType::Numeric(Numeric(number as u64)));
+        }
+    }
+    if value.is_bigint() {
+        if let Ok(number) = u64::try_from(value.clone()) {
+            return Ok(PactType::Numeric(Numeric(number)));
+        }
+    }
+    if Array::is_array(value) {
+        let list = Array::from(value)
+            .iter()
+            .map(|item| to_pact_arg(&item))
+            .collect::<Result<Vec<PactType>, TRNNutError>>()?;
+        return Ok(PactType::List(list));
+    }
+    Err(TRNNutError::new(
+        "INVALID_ARGUMENT",
+        format!(
+            "unsupported argument {value:?}, expected an unsigned integer, string, bytes or array"
+        ),
+    ))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const CONSTRAINT_TYPE_DEFINITION: &str = r#"
+/** A value compared with, strings and bytes (as 0x prefixed hex) are both string-like to Pact */
+export type ConstraintValue =
+    | { readonly numeric: number | bigint }
+    | { readonly string: string }
+    | { readonly bytes: string }
+    | { readonly list: ReadonlyArray<ConstraintValue> };
+
+/** A comparison operator, `in` and `notIn` compare with a list */
+export type CompareOp = "eq" | "ne" | "gt" | "ge" | "lt" | "le" | "in" | "notIn";
+
+/** A constraint on the arguments of a method call, see `buildConstraints` */
+export type ConstraintExpr =
+    /** Holds when every constraint holds */
+    | { readonly all: ReadonlyArray<ConstraintExpr> }
+    /** Holds when any constraint holds */
+    | { readonly any: ReadonlyArray<ConstraintExpr> }
+    /** Compares argument `arg` with a value */
+    | { readonly arg: number; readonly op: CompareOp; readonly value: ConstraintValue }
+    /** Compares argument `arg` with argument `otherArg` */
+    | { readonly arg: number; readonly op: CompareOp; readonly otherArg: number };
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ConstraintExpr")]
+    pub type ConstraintExprJS;
+}
+
+/// A `CompareOp`, named as the `Arg` builder methods
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CompareOpName {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    In,
+    NotIn,
+}
+
+impl From<CompareOpName> for CompareOp {
+    fn from(op: CompareOpName) -> Self {
+        match op {
+            CompareOpName::Eq => CompareOp::Eq,
+            CompareOpName::Ne => CompareOp::Ne,
+            CompareOpName::Gt => CompareOp::Gt,
+            CompareOpName::Ge => CompareOp::Ge,
+            CompareOpName::Lt => CompareOp::Lt,
+            CompareOpName::Le => CompareOp::Le,
+            CompareOpName::In => CompareOp::In,
+            CompareOpName::NotIn => CompareOp::NotIn,
+        }
+    }
+}
+
+impl From<CompareOp> for CompareOpName {
+    fn from(op: CompareOp) -> Self {
+        match op {
+            CompareOp::Eq => CompareOpName::Eq,
+            CompareOp::Ne => CompareOpName::Ne,
+            CompareOp::Gt => CompareOpName::Gt,
+            CompareOp::Ge => CompareOpName::Ge,
+            CompareOp::Lt => CompareOpName::Lt,
+            CompareOp::Le => CompareOpName::Le,
+            CompareOp::In => CompareOpName::In,
+            CompareOp::NotIn => CompareOpName::NotIn,
+        }
+    }
+}
+
+/// A `ConstraintExpr`, its fields select the kind of constraint
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ConstraintExpr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    all: Option<Vec<ConstraintExpr>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    any: Option<Vec<ConstraintExpr>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arg: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    op: Option<CompareOpName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<PactValueJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    other_arg: Option<u8>,
+}
+
+impl TryFrom<ConstraintExpr> for Constraint {
+    type Error = TRNNutError;
+
+    fn try_from(expr: ConstraintExpr) -> Result<Self, Self::Error> {
+        let constraints = |exprs: Vec<ConstraintExpr>| {
+            exprs
+                .into_iter()
+                .map(Constraint::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let compare = |arg, op: CompareOpName, rhs| {
+            Constraint::Compare(Comparison {
+                arg,
+                op: op.into(),
+                rhs,
+            })
+        };
+        match (
+            expr.all,
+            expr.any,
+            expr.arg,
+            expr.op,
+            expr.value,
+            expr.other_arg,
+        ) {
+            (Some(all), None, None, None, None, None) => Ok(Constraint::All(constraints(all)?)),
+            (None, Some(any), None, None, None, None) => Ok(Constraint::Any(constraints(any)?)),
+            (None, None, Some(arg), Some(op), Some(value), None) => {
+                let value = PactType::try_from(&value)
+                    .map_err(|err| TRNNutError::new("INVALID_CONSTRAINTS", err))?;
+                Ok(compare(arg, op, Operand::Value(value)))
+            }
+            (None, None, Some(arg), Some(op), None, Some(other_arg)) => {
+                Ok(compare(arg, op, Operand::Arg(other_arg)))
+            }
+            _ => Err(TRNNutError::new(
+                "INVALID_CONSTRAINTS",
+                "a constraint has either all, any, or an arg, op and a value or otherArg",
+            )),
+        }
+    }
+}
+
+impl From<&Constraint> for ConstraintExpr {
+    fn from(constraint: &Constraint) -> Self {
+        match constraint {
+            Constraint::All(constraints) => ConstraintExpr {
+                all: Some(constraints.iter().map(ConstraintExpr::from).collect()),
+                ..ConstraintExpr::default()
+            },
+            Constraint::Any(constraints) => ConstraintExpr {
+                any: Some(constraints.iter().map(ConstraintExpr::from).collect()),
+                ..ConstraintExpr::default()
+            },
+            Constraint::Compare(Comparison { arg, op, rhs }) => ConstraintExpr {
+                arg: Some(*arg),
+                op: Some((*op).into()),
+                value: match rhs {
+                    Operand::Value(value) => Some(PactValueJson::from(value)),
+                    Operand::Arg(_) => None,
+                },
+                other_arg: match rhs {
+                    Operand::Arg(other_arg) => Some(*other_arg),
+                    Operand::Value(_) => None,
+                },
+                ..ConstraintExpr::default()
+            },
+        }
+    }
+}
+
+impl From<ConstraintError> for TRNNutError {
+    fn from(err: ConstraintError) -> Self {
+        let code = match err {
+            ConstraintError::TooLong => "CONSTRAINTS_TOO_LONG",
+            _ => "INVALID_CONSTRAINTS",
+        };
+        Self::new(code, err)
+    }
+}
+
+/// Compile a `ConstraintExpr` into constraints bytes
+fn compile_constraints(expr: &ConstraintExprJS) -> Result<Vec<u8>, TRNNutError> {
+    let expr: ConstraintExpr = from_js(expr.into(), "INVALID_CONSTRAINTS", "constraint")?;
+    Ok(Constraint::try_from(expr)?.to_bytes()?)
+}
+
+/// Build the constraints bytes of a method from a `ConstraintExpr`
+///
+/// Throws a `TRNNutError` if the expression is malformed, or Pact can't evaluate it
+#[wasm_bindgen(js_name = buildConstraints)]
+pub fn build_constraints(expr: &ConstraintExprJS) -> Result<Vec<u8>, JsValue> {
+    compile_constraints(expr).map_err(Into::into)
+}
+
+/// Describe constraints bytes as the `ConstraintExpr` they evaluate
+/// Numeric values are described as bigints
+///
+/// Throws a `TRNNutError` if they are not a Pact contract, or one with no `ConstraintExpr` equivalent
+#[wasm_bindgen(js_name = describeConstraints)]
+pub fn describe_constraints(constraints: &[u8]) -> Result<ConstraintExprJS, JsValue> {
+    let pact = PactContract::decode(constraints).map_err(|_| {
+        TRNNutError::new(
+            "INVALID_CONSTRAINTS",
+            "constraints are not a valid Pact contract",
+        )
+    })?;
+    let constraint = Constraint::decompile(&pact).map_err(TRNNutError::from)?;
+    to_js(&ConstraintExpr::from(&constraint))
 }
 
 /// A js handle/wrapper for a rust versioned trnnut struct
@@ -69,91 +600,272 @@ pub struct TRNNutJS(TRNNutV0);
 impl TRNNutJS {
     #[wasm_bindgen(constructor)]
     /// Create a new TRNNut, it is always v0 for now
-    pub fn new(modules: &JsValue) -> Self {
+    pub fn new(modules: &ModuleConfigsJS) -> Result<TRNNutJS, JsValue> {
         console_error_panic_hook::set_once();
 
-        let modules: Vec<Module> = serde_wasm_bindgen::from_value(modules.clone()).expect("Deserialization of modules failed");
+        let modules: Vec<ModuleConfig> = from_js(modules.into(), "INVALID_MODULES", "modules")?;
 
-        let trnnut: TRNNutV0 = TRNNutV0 { modules };
-        TRNNutJS(trnnut)
+        let trnnut: TRNNutV0 = TRNNutV0 {
+            modules: modules.into_iter().map(Module::from).collect(),
+        };
+        Ok(TRNNutJS(trnnut))
     }
 
+    /// The module granting or denying `module` as a `ModuleConfig` object
     #[wasm_bindgen(js_name = getModule)]
-    pub fn get_module(&self, module: &str) -> JsValue {
-        self.0.get_module(module)
-            .map(|module| serde_wasm_bindgen::to_value(&module).unwrap_or(JsValue::UNDEFINED))
-            .unwrap_or(JsValue::UNDEFINED)
+    pub fn get_module(&self, module: &str) -> Result<Option<ModuleConfigJS>, JsValue> {
+        self.0
+            .get_module(module)
+            .map(|module| to_js(&ModuleConfig::from(module)))
+            .transpose()
+    }
+
+    /// Add `module`, unless a module of the same name exists
+    #[wasm_bindgen(js_name = addModule)]
+    pub fn add_module(&mut self, module: &ModuleConfigJS) -> Result<(), JsValue> {
+        let module: ModuleConfig = from_js(module.into(), "INVALID_MODULES", "module")?;
+        self.0
+            .add_module(module.into())
+            .map_err(|err| TRNNutError::from(err).into())
+    }
+
+    /// Remove the module named `module`, returning it as a `ModuleConfig` object
+    ///
+    /// Throws a `TRNNutError` if the module does not exist or is the last module
+    #[wasm_bindgen(js_name = removeModule)]
+    pub fn remove_module(&mut self, module: &str) -> Result<ModuleConfigJS, JsValue> {
+        let module = self.0.remove_module(module).map_err(TRNNutError::from)?;
+        to_js(&ModuleConfig::from(&module))
+    }
+
+    /// Add `method` to `module`, returning the method of the same name it replaces
+    #[wasm_bindgen(js_name = upsertMethod)]
+    pub fn upsert_method(
+        &mut self,
+        module: &str,
+        method: &MethodConfigJS,
+    ) -> Result<Option<MethodConfigJS>, JsValue> {
+        let method: MethodConfig = from_js(method.into(), "INVALID_MODULES", "method")?;
+        self.0
+            .upsert_method(module, method.into())
+            .map_err(TRNNutError::from)?
+            .map(|method| to_js(&MethodConfig::from(&method)))
+            .transpose()
+    }
+
+    /// Remove the method named `method` of `module`, returning it as a `MethodConfig` object
+    ///
+    /// Throws a `TRNNutError` if the method does not exist or is the last method of `module`
+    #[wasm_bindgen(js_name = removeMethod)]
+    pub fn remove_method(&mut self, module: &str, method: &str) -> Result<MethodConfigJS, JsValue> {
+        let method = self
+            .0
+            .remove_method(module, method)
+            .map_err(TRNNutError::from)?;
+        to_js(&MethodConfig::from(&method))
+    }
+
+    /// Set the cooldown of `method` of `module`, or of `module` itself when `method` is undefined
+    #[wasm_bindgen(js_name = setCooldown)]
+    pub fn set_cooldown(
+        &mut self,
+        module: &str,
+        method: Option<String>,
+        block_cooldown: Option<u32>,
+    ) -> Result<(), JsValue> {
+        self.0
+            .set_cooldown(module, method.as_deref(), block_cooldown)
+            .map_err(|err| TRNNutError::from(err).into())
+    }
+
+    /// Set the constraints of `method` of `module`, clearing them when `constraints` is undefined
+    #[wasm_bindgen(js_name = setConstraints)]
+    pub fn set_constraints(
+        &mut self,
+        module: &str,
+        method: &str,
+        constraints: Option<Vec<u8>>,
+    ) -> Result<(), JsValue> {
+        self.0
+            .set_constraints(module, method, constraints)
+            .map_err(|err| TRNNutError::from(err).into())
+    }
+
+    /// Validate a call of `method` of `module` with `args`, as the runtime would
+    ///
+    /// Throws a `TRNNutError` if `args` is not an array or an argument can't be converted
+    pub fn validate(
+        &self,
+        module: &str,
+        method: &str,
+        args: &ValidationArgsJS,
+    ) -> Result<ValidationResultJS, JsValue> {
+        if !Array::is_array(args) {
+            return Err(
+                TRNNutError::new("INVALID_ARGUMENT", "expected an array of arguments").into(),
+            );
+        }
+        let args = Array::from(args)
+            .iter()
+            .map(|arg| to_pact_arg(&arg))
+            .collect::<Result<Vec<PactType>, TRNNutError>>()?;
+        to_js(&ValidationResult::from(
+            self.0.validate_module(module, method, &args),
+        ))
     }
 
     /// Encode the trnnut into bytes
-    pub fn encode(&mut self) -> Vec<u8> {
-        self.0.encode()
+    ///
+    /// Throws a `TRNNutError` if it exceeds the encoding limits or has invalid constraints
+    pub fn encode(&mut self) -> Result<Vec<u8>, JsValue> {
+        for module in &self.0.modules {
+            for method in &module.methods {
+                check_constraints(Some(&module.name), method)?;
+            }
+        }
+        self.0
+            .try_encode()
+            .map_err(|err| TRNNutError::from(err).into())
     }
 
     /// Decode a version 0 trnnut from `input` bytes
     pub fn decode(input: &[u8]) -> Result<TRNNutJS, JsValue> {
-        match TRNNutV0::decode(&mut &input[..]) {
-            Ok(trnnut) => Ok(TRNNutJS(trnnut)),
-            Err(err) => {
-                log(&format!("failed decoding: {:?}", err));
-                Err(JsValue::undefined())
-            }
+        TRNNutV0::decode(&mut &input[..])
+            .map(TRNNutJS)
+            .map_err(|err| TRNNutError::new("DECODE", format!("failed decoding: {err}")).into())
+    }
+}
+
+/// A js handle/wrapper for a rust module, built like the rust `Module`
+#[wasm_bindgen(js_name = Module)]
+pub struct ModuleJS(Module);
+
+#[wasm_bindgen(js_class = Module)]
+impl ModuleJS {
+    #[wasm_bindgen(constructor)]
+    /// Create a new Module granting no methods
+    pub fn new(name: &str) -> Result<ModuleJS, JsValue> {
+        console_error_panic_hook::set_once();
+
+        let module = Module::try_new(name).map_err(|err| TRNNutError::new("INVALID_NAME", err))?;
+        Ok(ModuleJS(module))
+    }
+
+    /// Set the block cooldown of the module
+    #[wasm_bindgen(js_name = blockCooldown)]
+    pub fn block_cooldown(self, block_cooldown: u32) -> ModuleJS {
+        ModuleJS(self.0.block_cooldown(block_cooldown))
+    }
+
+    /// Add `method` to the methods of the module
+    pub fn method(mut self, method: MethodJS) -> ModuleJS {
+        self.0.methods.push(method.0);
+        self
+    }
+
+    /// Deny every method of the module instead of granting them
+    pub fn deny(self) -> ModuleJS {
+        ModuleJS(self.0.deny())
+    }
+
+    /// Create a Module from a `ModuleConfig` object
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(config: &ModuleConfigJS) -> Result<ModuleJS, JsValue> {
+        let config: ModuleConfig = from_js(config.into(), "INVALID_MODULES", "module")?;
+        Ok(ModuleJS(config.into()))
+    }
+
+    /// The module as a `ModuleConfig` object
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> Result<ModuleConfigJS, JsValue> {
+        to_js(&ModuleConfig::from(&self.0))
+    }
+
+    /// Encode the module into bytes
+    ///
+    /// Throws a `TRNNutError` if it exceeds the encoding limits or has invalid constraints
+    pub fn encode(&self) -> Result<Vec<u8>, JsValue> {
+        for method in &self.0.methods {
+            check_constraints(Some(&self.0.name), method)?;
         }
+        self.0
+            .try_encode()
+            .map_err(|err| TRNNutError::from(err).into())
+    }
+
+    /// Decode a version 0 module from `input` bytes
+    pub fn decode(input: &[u8]) -> Result<ModuleJS, JsValue> {
+        Module::decode(&mut &input[..])
+            .map(ModuleJS)
+            .map_err(|err| TRNNutError::new("DECODE", format!("failed decoding: {err}")).into())
     }
 }
 
-// /// A js handle/wrapper for a rust versioned module
-// #[derive(serde::Deserialize)]
-// #[wasm_bindgen(js_name = Module)]
-// pub struct ModuleJS(Module);
-
-// #[wasm_bindgen(js_class = Module)]
-// #[allow(irrefutable_let_patterns)]
-// impl ModuleJS {
-//     #[wasm_bindgen(constructor)]
-//     /// Create a new Module
-//     pub fn new(name: &str, block_cooldown: u32, methods: JsValue) -> Self {
-//         console_error_panic_hook::set_once();
-
-//         let methods: Vec<Method> = methods
-//             .into_serde()
-//             .expect("Deserialization of methods failed");
-
-//         let module = Module::new(name)
-//             .block_cooldown(block_cooldown)
-//             .methods(vec![]);
-
-//         ModuleJS(module)
-//     }
-
-//     /// Encode the module into bytes
-//     pub fn encode(&mut self) -> Vec<u8> {
-//         self.0.encode()
-//     }
-// }
-
-// /// A js handle/wrapper for a rust versioned Method struct
-// #[derive(serde::Deserialize)]
-// #[wasm_bindgen(js_name = Method)]
-// pub struct MethodJS(Method);
-
-// #[wasm_bindgen(js_class = Method)]
-// #[allow(irrefutable_let_patterns)]
-// impl MethodJS {
-//     #[wasm_bindgen(constructor)]
-//     /// Create a new Method
-//     pub fn new(name: &str, block_cooldown: u32, constraints: Vec<u8>) -> Self {
-//         console_error_panic_hook::set_once();
-        
-//         let method = Method::new(name)
-//             .block_cooldown(block_cooldown)
-//             .constraints(constraints);
-        
-//         MethodJS(method)
-//     }
-
-//     /// Encode the method into bytes
-//     pub fn encode(&mut self) -> Vec<u8> {
-//         self.0.encode()
-//     }
-// }
+/// A js handle/wrapper for a rust method, built like the rust `Method`
+#[wasm_bindgen(js_name = Method)]
+pub struct MethodJS(Method);
+
+#[wasm_bindgen(js_class = Method)]
+impl MethodJS {
+    #[wasm_bindgen(constructor)]
+    /// Create a new Method, granted without cooldown or constraints
+    pub fn new(name: &str) -> Result<MethodJS, JsValue> {
+        console_error_panic_hook::set_once();
+
+        let method = Method::try_new(name).map_err(|err| TRNNutError::new("INVALID_NAME", err))?;
+        Ok(MethodJS(method))
+    }
+
+    /// Set the block cooldown of the method
+    #[wasm_bindgen(js_name = blockCooldown)]
+    pub fn block_cooldown(self, block_cooldown: u32) -> MethodJS {
+        MethodJS(self.0.block_cooldown(block_cooldown))
+    }
+
+    /// Set the constraints of the method, which must be an encoded Pact contract
+    pub fn constraints(self, constraints: Vec<u8>) -> Result<MethodJS, JsValue> {
+        let method = self.0.constraints(constraints);
+        check_constraints(None, &method)?;
+        Ok(MethodJS(method))
+    }
+
+    /// Set the constraints of the method to those built from a `ConstraintExpr`
+    pub fn constrain(self, expr: &ConstraintExprJS) -> Result<MethodJS, JsValue> {
+        Ok(MethodJS(self.0.constraints(compile_constraints(expr)?)))
+    }
+
+    /// Deny the method instead of granting it
+    pub fn deny(self) -> MethodJS {
+        MethodJS(self.0.deny())
+    }
+
+    /// Create a Method from a `MethodConfig` object
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(config: &MethodConfigJS) -> Result<MethodJS, JsValue> {
+        let config: MethodConfig = from_js(config.into(), "INVALID_MODULES", "method")?;
+        Ok(MethodJS(config.into()))
+    }
+
+    /// The method as a `MethodConfig` object
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> Result<MethodConfigJS, JsValue> {
+        to_js(&MethodConfig::from(&self.0))
+    }
+
+    /// Encode the method into bytes
+    ///
+    /// Throws a `TRNNutError` if it exceeds the encoding limits or has invalid constraints
+    pub fn encode(&self) -> Result<Vec<u8>, JsValue> {
+        check_constraints(None, &self.0)?;
+        self.0
+            .try_encode()
+            .map_err(|err| TRNNutError::from(err).into())
+    }
+
+    /// Decode a method from `input` bytes
+    pub fn decode(input: &[u8]) -> Result<MethodJS, JsValue> {
+        Method::decode(&mut &input[..])
+            .map(MethodJS)
+            .map_err(|err| TRNNutError::new("DECODE", format!("failed decoding: {err}")).into())
+    }
+}
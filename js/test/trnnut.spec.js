@@ -1,6 +1,4 @@
-const TRNNut = require('../libNode/trnnut').TRNNut;
-// const Module = require('../libNode/trnnut').Module;
-// const Method = require('../libNode/trnnut').Method;
+const { TRNNut, Module, Method, buildConstraints, describeConstraints } = require('../libNode/trnnut');
 
 // The test used is same as it_works_decode_with_method_cooldown in rust
 const encodedTRNNut = new Uint8Array([
@@ -16,7 +14,7 @@ describe("trnnut", () => {
     expect(trnnut.encode()).toEqual(encodedTRNNut);
     const module = trnnut.getModule("module_test");
     expect(module.name).toEqual('module_test');
-    expect(module.block_cooldown).toEqual(86400);
+    expect(module.blockCooldown).toEqual(86400);
     expect(module.methods[0].name).toContain("method_test");
   });
 
@@ -51,16 +49,10 @@ describe("trnnut", () => {
       }
     ];
 
-    // const modules = [
-    //   new Module("test_module_check1", 270549120, [
-    //     new Method("test_method_check1", 270549120, []),
-    //   ]),
-    // ];
-
     const trnnut = new TRNNut(modules);
     const module = trnnut.getModule("test_module_check1");
     expect(module.name).toEqual('test_module_check1');
-    expect(module.block_cooldown).toEqual(270549120);
+    expect(module.blockCooldown).toEqual(270549120);
     expect(module.methods[0].name).toContain("test_method_check1");
   });
 
@@ -70,6 +62,25 @@ describe("trnnut", () => {
       expect(module).toEqual(undefined);
   });
 
+  test("it validates calls", () => {
+    const trnnut = TRNNut.decode(encodedTRNNut);
+
+    expect(trnnut.validate("module_test", "method_test", [])).toEqual({ ok: true });
+    expect(trnnut.validate("module_test", "method_test", [1, 2n, "AUD", new Uint8Array([1]), [3]]))
+      .toEqual({ ok: true });
+    expect(trnnut.validate("module_test1", "method_test", [])).toEqual({
+      ok: false,
+      domain: "module",
+      reason: "TRNNut does not grant permission for module",
+    });
+    expect(trnnut.validate("module_test", "method_test1", [])).toEqual({
+      ok: false,
+      domain: "method",
+      reason: "TRNNut does not grant permission for method",
+    });
+    expect(() => trnnut.validate("module_test", "method_test", [-1])).toThrow();
+  });
+
   test("create instance of trnnut with constraint payload", () => {
     const constraints = new Uint8Array([
       27, 137,  65,  29, 182,  25, 157,  61,
@@ -95,8 +106,136 @@ describe("trnnut", () => {
 
     let extract_module = trnnut.getModule("Balances");
     expect(extract_module.name).toEqual('Balances');
-    expect(extract_module.block_cooldown).toEqual(0);
+    expect(extract_module.blockCooldown).toEqual(0);
     expect(extract_module.methods[0].name).toContain("transfer");
     expect(extract_module.methods[0].constraints).toEqual([...constraints])
   });
+
+  test("it throws TRNNutErrors", () => {
+    const thrown = (f) => {
+      try {
+        f();
+      } catch (err) {
+        return err;
+      }
+    };
+
+    const decodeError = thrown(() => TRNNut.decode(new Uint8Array([0, 0, 1])));
+    expect(decodeError).toBeInstanceOf(Error);
+    expect(decodeError.name).toEqual("TRNNutError");
+    expect(decodeError.code).toEqual("DECODE");
+
+    expect(thrown(() => new TRNNut([{ name: "a".repeat(33), methods: [] }])).code)
+      .toEqual("INVALID_MODULES");
+    expect(thrown(() => new TRNNut([]).encode()).code).toEqual("NO_MODULES");
+    expect(thrown(() => new TRNNut([{ name: "Balances", methods: [] }]).encode()).code)
+      .toEqual("NO_METHODS");
+    const invalidConstraints = new TRNNut([
+      { name: "Balances", methods: [{ name: "transfer", constraints: [1, 2, 3] }] },
+    ]);
+    expect(thrown(() => invalidConstraints.encode()).code).toEqual("INVALID_CONSTRAINTS");
+    expect(thrown(() => TRNNut.decode(encodedTRNNut).validate("module_test", "method_test", [-1])).code)
+      .toEqual("INVALID_ARGUMENT");
+    expect(thrown(() => TRNNut.decode(encodedTRNNut).validate("module_test", "method_test", 1)).code)
+      .toEqual("INVALID_ARGUMENT");
+  });
+
+  test("it builds modules and methods", () => {
+    const module = new Module("module_test")
+      .blockCooldown(86400)
+      .method(new Method("method_test"));
+
+    expect(module.toObject()).toEqual({
+      name: "module_test",
+      blockCooldown: 86400,
+      methods: [{ name: "method_test" }],
+    });
+    expect(new TRNNut([module.toObject()]).encode()).toEqual(encodedTRNNut);
+    expect(module.encode()).toEqual(encodedTRNNut.slice(3));
+    expect(Module.decode(encodedTRNNut.slice(3)).toObject()).toEqual(module.toObject());
+    expect(Method.decode(encodedTRNNut.slice(40)).encode()).toEqual(encodedTRNNut.slice(40));
+
+    const method = Method.fromObject({ name: "transfer", blockCooldown: 5 }).deny();
+    expect(method.toObject()).toEqual({ name: "transfer", blockCooldown: 5, deny: true });
+    expect(Module.fromObject({ name: "Balances", methods: [method.toObject()] }).toObject().methods)
+      .toEqual([{ name: "transfer", blockCooldown: 5, deny: true }]);
+
+    expect(() => new Method("a".repeat(33))).toThrow("name is 33 bytes, longer than 32 bytes");
+    expect(() => new Method("transfer").constraints(new Uint8Array([1, 2, 3]))).toThrow();
+  });
+
+  test("it edits modules and methods", () => {
+    const trnnut = new TRNNut([{ name: "Balances", methods: [{ name: "transfer" }] }]);
+    const thrown = (f) => {
+      try {
+        f();
+      } catch (err) {
+        return err;
+      }
+    };
+
+    trnnut.addModule({ name: "Nft", methods: [{ name: "mint" }] });
+    expect(thrown(() => trnnut.addModule({ name: "Nft", methods: [] })).code).toEqual("DUPLICATE_MODULE");
+    expect(trnnut.upsertMethod("Balances", { name: "transfer", blockCooldown: 10 })).toEqual({ name: "transfer" });
+    expect(trnnut.upsertMethod("Balances", { name: "burn" })).toEqual(undefined);
+    expect(thrown(() => trnnut.upsertMethod("Staking", { name: "bond" })).code).toEqual("NO_SUCH_MODULE");
+
+    trnnut.setCooldown("Balances", undefined, 100);
+    trnnut.setCooldown("Balances", "burn", 5);
+    expect(thrown(() => trnnut.setConstraints("Balances", "burn", new Uint8Array([1, 2, 3]))).code)
+      .toEqual("INVALID_CONSTRAINTS");
+    expect(trnnut.getModule("Balances")).toEqual({
+      name: "Balances",
+      blockCooldown: 100,
+      methods: [
+        { name: "transfer", blockCooldown: 10 },
+        { name: "burn", blockCooldown: 5 },
+      ],
+    });
+
+    expect(thrown(() => trnnut.addModule({ name: "Staking", methods: [] })).code).toEqual("NO_METHODS");
+    expect(thrown(() => trnnut.addModule({ name: "Staking", methods: [{ name: "bond" }], deny: true })).code)
+      .toEqual("MODULE_DENY_UNSUPPORTED");
+
+    expect(trnnut.removeMethod("Balances", "burn")).toEqual({ name: "burn", blockCooldown: 5 });
+    expect(thrown(() => trnnut.removeMethod("Balances", "burn")).code).toEqual("NO_SUCH_METHOD");
+    expect(thrown(() => trnnut.removeMethod("Balances", "transfer")).code).toEqual("NO_METHODS");
+    expect(trnnut.removeModule("Nft")).toEqual({ name: "Nft", methods: [{ name: "mint" }] });
+    expect(thrown(() => trnnut.removeModule("Nft")).code).toEqual("NO_SUCH_MODULE");
+    expect(thrown(() => trnnut.removeModule("Balances")).code).toEqual("NO_MODULES");
+  });
+
+  test("it builds and describes constraints", () => {
+    const expr = {
+      all: [
+        { arg: 0, op: "le", value: { numeric: 1000 } },
+        { any: [{ arg: 1, op: "eq", value: { string: "AUD" } }, { arg: 1, op: "eq", otherArg: 2 }] },
+      ],
+    };
+    const constraints = buildConstraints(expr);
+
+    expect(describeConstraints(constraints)).toEqual({
+      all: [
+        { arg: 0, op: "le", value: { numeric: 1000n } },
+        { any: [{ arg: 1, op: "eq", value: { string: "AUD" } }, { arg: 1, op: "eq", otherArg: 2 }] },
+      ],
+    });
+    expect(describeConstraints(buildConstraints({ arg: 0, op: "notIn", value: { list: [{ numeric: 1 }] } })))
+      .toEqual({ arg: 0, op: "notIn", value: { list: [{ numeric: 1n }] } });
+    const max = { arg: 0, op: "le", value: { numeric: 2n ** 64n - 1n } };
+    expect(describeConstraints(buildConstraints(max))).toEqual(max);
+
+    const trnnut = new TRNNut([
+      new Module("Balances").method(new Method("transfer").constrain(expr)).toObject(),
+    ]);
+    expect(trnnut.validate("Balances", "transfer", [1000, "AUD", "NZD"])).toEqual({ ok: true });
+    expect(trnnut.validate("Balances", "transfer", [1001, "AUD", "NZD"]).domain).toEqual("methodArguments");
+
+    expect(() => buildConstraints({ arg: 0, op: "le" })).toThrow("a constraint has either all, any");
+    expect(() => buildConstraints({ all: [{ arg: 0, op: "le", value: { numeric: 1 } }], arg: 0 }))
+      .toThrow("a constraint has either all, any");
+    expect(() => buildConstraints({ any: [], op: "le" })).toThrow("a constraint has either all, any");
+    expect(() => buildConstraints({ arg: 16, op: "le", value: { numeric: 1 } })).toThrow("argument 16 is out of range");
+    expect(() => describeConstraints(new Uint8Array([1, 2, 3]))).toThrow();
+  });
 });
@@ -0,0 +1,152 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Attenuation
+//!
+//! Checks that a delegated TRNNut grants no more than its parent
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+};
+
+use crate::{
+    method::Method,
+    module::Module,
+    pattern::{display_name, representative_names},
+    trnnut::granted_module,
+};
+
+/// A permission granted by a child TRNNut which its parent does not grant
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttenuationViolation {
+    ModuleNotGranted {
+        module: String,
+    },
+    MethodNotGranted {
+        module: String,
+        method: String,
+    },
+    CooldownTooShort {
+        module: String,
+        method: Option<String>,
+    },
+    ConstraintsNotAttenuated {
+        module: String,
+        method: String,
+    },
+}
+
+impl Display for AttenuationViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModuleNotGranted { module } => {
+                write!(f, "module {module} is not granted by the parent")
+            }
+            Self::MethodNotGranted { module, method } => {
+                write!(f, "method {module}::{method} is not granted by the parent")
+            }
+            Self::CooldownTooShort {
+                module,
+                method: None,
+            } => write!(f, "module {module} has a shorter cooldown than the parent"),
+            Self::CooldownTooShort {
+                module,
+                method: Some(method),
+            } => write!(
+                f,
+                "method {module}::{method} has a shorter cooldown than the parent"
+            ),
+            Self::ConstraintsNotAttenuated { module, method } => write!(
+                f,
+                "method {module}::{method} does not carry the parent's constraints"
+            ),
+        }
+    }
+}
+
+/// A child cooldown must be at least as long as the parent's
+fn cooldown_is_attenuated(child: Option<u32>, parent: Option<u32>) -> bool {
+    match (child, parent) {
+        (_, None) => true,
+        (Some(child), Some(parent)) => child >= parent,
+        (None, Some(_)) => false,
+    }
+}
+
+/// Constraints can't be compared for strictness in general, so a child must
+/// carry exactly the parent's constraints whenever the parent has any
+fn constraints_are_attenuated(child: &Method, parent: &Method) -> bool {
+    match parent.constraints.as_ref().filter(|c| !c.is_empty()) {
+        Some(parent_constraints) => child.constraints.as_ref() == Some(parent_constraints),
+        None => true,
+    }
+}
+
+/// Checks every method `child` grants is granted by `parent`
+fn module_is_subset_of(
+    module_name: &str,
+    child: &Module,
+    parent: &Module,
+) -> Result<(), AttenuationViolation> {
+    if !cooldown_is_attenuated(child.block_cooldown, parent.block_cooldown) {
+        return Err(AttenuationViolation::CooldownTooShort {
+            module: module_name.to_string(),
+            method: None,
+        });
+    }
+
+    let names = representative_names(
+        child
+            .methods
+            .iter()
+            .chain(&parent.methods)
+            .map(|m| m.name.as_str()),
+    );
+    for name in &names {
+        let Some(child_method) = child.granted_method(name) else {
+            continue;
+        };
+        let parent_method =
+            parent
+                .granted_method(name)
+                .ok_or_else(|| AttenuationViolation::MethodNotGranted {
+                    module: module_name.to_string(),
+                    method: display_name(name),
+                })?;
+        if !cooldown_is_attenuated(child_method.block_cooldown, parent_method.block_cooldown) {
+            return Err(AttenuationViolation::CooldownTooShort {
+                module: module_name.to_string(),
+                method: Some(display_name(name)),
+            });
+        }
+        if !constraints_are_attenuated(child_method, parent_method) {
+            return Err(AttenuationViolation::ConstraintsNotAttenuated {
+                module: module_name.to_string(),
+                method: display_name(name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks every module and method `child` grants is granted by `parent`
+/// Every name which may resolve to different entries is checked, see `representative_names`
+pub(crate) fn modules_are_subset_of(
+    child: &[Module],
+    parent: &[Module],
+) -> Result<(), AttenuationViolation> {
+    let names = representative_names(child.iter().chain(parent).map(|m| m.name.as_str()));
+    for name in &names {
+        let Some(child_module) = granted_module(child, name) else {
+            continue;
+        };
+        let module_name = display_name(name);
+        let parent_module =
+            granted_module(parent, name).ok_or_else(|| AttenuationViolation::ModuleNotGranted {
+                module: module_name.clone(),
+            })?;
+        module_is_subset_of(&module_name, child_module, parent_module)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,380 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - CLI
+//!
+//! Encode, decode, validate and compare TRNNuts from the command line
+//!
+
+use std::{fmt::Write, fs, path::Path, process::ExitCode};
+
+use base64::Engine;
+use clap::{Parser, Subcommand, ValueEnum};
+use codec::Decode;
+use pact::types::{Numeric, PactType, StringLike};
+use trnnut_rs::{
+    method::Method, module::Module, StrictDecode, TRNNut, TRNNutV0, TRNNutV1, TryEncode,
+};
+
+#[derive(Parser)]
+#[command(name = "trnnut", version, about = "Encode, decode and inspect TRNNuts")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decode a TRNNut and print its permissions
+    Decode {
+        /// Encoded TRNNut, as hex, base64 or a file path
+        input: String,
+        #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+        format: InputFormat,
+        /// Reject trailing bytes and non-canonical encodings
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Encode a TRNNut from a JSON or YAML description of its modules
+    Encode {
+        /// JSON or YAML file, `-` reads standard input
+        input: String,
+        #[arg(long, value_enum, default_value_t = SourceFormat::Json)]
+        from: SourceFormat,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Hex)]
+        to: OutputFormat,
+        /// TRNNut version to encode
+        #[arg(long, default_value_t = 0)]
+        version: u16,
+    },
+    /// Check whether a TRNNut permits calling a runtime method
+    Validate {
+        /// Encoded TRNNut, as hex, base64 or a file path
+        trnnut: String,
+        module: String,
+        method: String,
+        /// Method arguments, numbers are numeric and anything else is a string
+        args: Vec<String>,
+        #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+        format: InputFormat,
+        /// Print which entries matched and where constraints failed
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Show the permissions which differ between two TRNNuts
+    Diff {
+        /// Encoded TRNNut, as hex, base64 or a file path
+        a: String,
+        /// Encoded TRNNut, as hex, base64 or a file path
+        b: String,
+        #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+        format: InputFormat,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum InputFormat {
+    /// A file path if it exists, otherwise hex if it parses, otherwise base64
+    Auto,
+    Hex,
+    Base64,
+    File,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum SourceFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Hex,
+    Base64,
+    Binary,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs `command`, returning whether it succeeded
+fn run(command: Command) -> Result<bool, String> {
+    match command {
+        Command::Decode {
+            input,
+            format,
+            strict,
+        } => {
+            let trnnut = decode(&read_input(&input, format)?, strict)?;
+            println!("{trnnut:#}");
+            Ok(true)
+        }
+        Command::Encode {
+            input,
+            from,
+            to,
+            version,
+        } => {
+            let source = if input == "-" {
+                std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?
+            } else {
+                fs::read_to_string(&input).map_err(|e| format!("{input}: {e}"))?
+            };
+            let encoded = encode(&source, from, version)?;
+            match to {
+                OutputFormat::Hex => println!("0x{}", hex::encode(encoded)),
+                OutputFormat::Base64 => {
+                    println!(
+                        "{}",
+                        base64::engine::general_purpose::STANDARD.encode(encoded)
+                    );
+                }
+                OutputFormat::Binary => {
+                    use std::io::Write as _;
+                    std::io::stdout()
+                        .write_all(&encoded)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            Ok(true)
+        }
+        Command::Validate {
+            trnnut,
+            module,
+            method,
+            args,
+            format,
+            explain,
+        } => {
+            let trnnut = decode(&read_input(&trnnut, format)?, false)?;
+            let args: Vec<PactType> = args.iter().map(|arg| parse_arg(arg)).collect();
+            let trace = trnnut.validate_module_explain(&module, &method, &args);
+            if explain {
+                println!("{trace}");
+            } else {
+                match &trace.result {
+                    Ok(()) => println!("permitted"),
+                    Err(err) => println!("{err}"),
+                }
+            }
+            Ok(trace.result.is_ok())
+        }
+        Command::Diff { a, b, format } => {
+            let a = decode(&read_input(&a, format)?, false)?;
+            let b = decode(&read_input(&b, format)?, false)?;
+            let changes = diff(a.modules(), b.modules());
+            for change in &changes {
+                println!("{change}");
+            }
+            Ok(changes.is_empty())
+        }
+    }
+}
+
+/// Reads encoded TRNNut bytes given as hex, base64 or a file path
+fn read_input(input: &str, format: InputFormat) -> Result<Vec<u8>, String> {
+    let from_hex = || hex::decode(input.trim().trim_start_matches("0x"));
+    let from_base64 = || base64::engine::general_purpose::STANDARD.decode(input.trim());
+    match format {
+        InputFormat::Hex => from_hex().map_err(|e| format!("invalid hex: {e}")),
+        InputFormat::Base64 => from_base64().map_err(|e| format!("invalid base64: {e}")),
+        InputFormat::File => fs::read(input).map_err(|e| format!("{input}: {e}")),
+        InputFormat::Auto if Path::new(input).is_file() => {
+            fs::read(input).map_err(|e| format!("{input}: {e}"))
+        }
+        InputFormat::Auto => from_hex()
+            .or_else(|_| from_base64())
+            .map_err(|_| format!("{input} is not a file, hex or base64")),
+    }
+}
+
+fn decode(bytes: &[u8], strict: bool) -> Result<TRNNut, String> {
+    if strict {
+        TRNNut::decode_strict(bytes)
+    } else {
+        TRNNut::decode(&mut &bytes[..])
+    }
+    .map_err(|e| format!("invalid TRNNut: {e}"))
+}
+
+fn encode(source: &str, from: SourceFormat, version: u16) -> Result<Vec<u8>, String> {
+    let trnnut: TRNNutV0 = match from {
+        SourceFormat::Json => serde_json::from_str(source).map_err(|e| e.to_string())?,
+        SourceFormat::Yaml => serde_yaml::from_str(source).map_err(|e| e.to_string())?,
+    };
+    let trnnut = match version {
+        0 => TRNNut::V0(trnnut),
+        1 => TRNNut::V1(TRNNutV1 {
+            modules: trnnut.modules,
+        }),
+        _ => return Err(format!("unsupported TRNNut version {version}")),
+    };
+    trnnut.try_encode().map_err(|e| e.to_string())
+}
+
+/// Numbers become numeric arguments, anything else a string argument
+fn parse_arg(arg: &str) -> PactType {
+    match arg.parse::<u64>() {
+        Ok(number) => PactType::Numeric(Numeric(number)),
+        Err(_) => PactType::StringLike(StringLike(arg.as_bytes().to_vec())),
+    }
+}
+
+fn describe_module(module: &Module) -> String {
+    let mut description = format!("module {}", module.name);
+    if module.deny {
+        description.push_str(", denied");
+    }
+    if let Some(cooldown) = module.block_cooldown {
+        let _ = write!(description, ", cooldown {cooldown}");
+    }
+    description
+}
+
+fn describe_method(method: &Method) -> String {
+    let mut description = format!("method {}", method.name);
+    if method.deny {
+        description.push_str(", denied");
+    }
+    if let Some(cooldown) = method.block_cooldown {
+        let _ = write!(description, ", cooldown {cooldown}");
+    }
+    if let Some(constraints) = method.constraints.as_ref().filter(|c| !c.is_empty()) {
+        let _ = write!(description, ", constraints 0x{}", hex::encode(constraints));
+    }
+    description
+}
+
+/// Lists the modules and methods added (`+`), removed (`-`) or changed (`~`) from `a` to `b`
+fn diff(a: &[Module], b: &[Module]) -> Vec<String> {
+    let mut changes = Vec::new();
+    for module in a {
+        match b.iter().find(|m| m.name == module.name) {
+            None => changes.push(format!("- {}", describe_module(module))),
+            Some(other) => {
+                if describe_module(module) != describe_module(other) {
+                    changes.push(format!(
+                        "~ {} -> {}",
+                        describe_module(module),
+                        describe_module(other)
+                    ));
+                }
+                for method in &module.methods {
+                    match other.methods.iter().find(|m| m.name == method.name) {
+                        None => {
+                            changes.push(format!("- {}: {}", module.name, describe_method(method)));
+                        }
+                        Some(other) if other != method => changes.push(format!(
+                            "~ {}: {} -> {}",
+                            module.name,
+                            describe_method(method),
+                            describe_method(other)
+                        )),
+                        Some(_) => {}
+                    }
+                }
+                for method in &other.methods {
+                    if !module.methods.iter().any(|m| m.name == method.name) {
+                        changes.push(format!("+ {}: {}", module.name, describe_method(method)));
+                    }
+                }
+            }
+        }
+    }
+    for module in b {
+        if !a.iter().any(|m| m.name == module.name) {
+            changes.push(format!("+ {}", describe_module(module)));
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, encode, parse_arg, read_input, InputFormat, SourceFormat};
+    use codec::Decode;
+    use pact::types::{Numeric, PactType, StringLike};
+    use trnnut_rs::{method::Method, module::Module, TRNNut};
+
+    #[test]
+    fn it_reads_hex_and_base64() {
+        assert_eq!(
+            read_input("0x0001ff", InputFormat::Auto),
+            Ok(vec![0, 1, 255])
+        );
+        assert_eq!(read_input("AAH/", InputFormat::Auto), Ok(vec![0, 1, 255]));
+        assert_eq!(read_input("AAH/", InputFormat::Base64), Ok(vec![0, 1, 255]));
+        assert!(read_input("AAH/", InputFormat::Hex).is_err());
+    }
+
+    #[test]
+    fn it_encodes_json_and_yaml() {
+        let json = r#"{"modules":[{"name":"Balances","block_cooldown":10,"methods":[
+            {"name":"transfer","block_cooldown":null,"constraints":null}]}]}"#;
+        let yaml = "modules:
+  - name: Balances
+    block_cooldown: 10
+    methods:
+      - name: transfer
+        block_cooldown: ~
+        constraints: ~
+";
+
+        let encoded = encode(json, SourceFormat::Json, 0).unwrap();
+        assert_eq!(encode(yaml, SourceFormat::Yaml, 0), Ok(encoded.clone()));
+
+        let trnnut = TRNNut::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(
+            format!("{trnnut:#}"),
+            "TRNNut v0, 73 bytes, 1 module\n  module Balances cooldown 10\n    method transfer"
+        );
+        assert_eq!(
+            encode(json, SourceFormat::Json, 1).map(|e| e[..2].to_vec()),
+            Ok(vec![1, 0])
+        );
+    }
+
+    #[test]
+    fn it_parses_args() {
+        assert_eq!(parse_arg("1000"), PactType::Numeric(Numeric(1000)));
+        assert_eq!(
+            parse_arg("AUD"),
+            PactType::StringLike(StringLike(b"AUD".to_vec()))
+        );
+    }
+
+    #[test]
+    fn it_diffs_modules_and_methods() {
+        let a = vec![
+            Module::new("Balances").methods(vec![Method::new("transfer"), Method::new("burn")]),
+            Module::new("Sudo").methods(vec![Method::new("sudo")]),
+        ];
+        let b = vec![
+            Module::new("Balances").block_cooldown(5).methods(vec![
+                Method::new("transfer").block_cooldown(1),
+                Method::new("mint"),
+            ]),
+            Module::new("Nft").methods(vec![Method::new("*")]),
+        ];
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![
+                "~ module Balances -> module Balances, cooldown 5",
+                "~ Balances: method transfer -> method transfer, cooldown 1",
+                "- Balances: method burn",
+                "+ Balances: method mint",
+                "- module Sudo",
+                "+ module Nft",
+            ]
+        );
+        assert!(diff(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Combine
+//!
+//! Intersection and union of TRNNut permission sets
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    constraint::Constraint,
+    method::Method,
+    module::Module,
+    pattern::{display_name, most_specific, representative_names},
+    trnnut::{granted_module, MAX_METHODS, MAX_MODULES},
+};
+
+/// Error which may occur while combining the permissions of two TRNNuts
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CombineError {
+    TooManyModules,
+    TooManyMethods {
+        module: String,
+    },
+    /// The combined module grants no methods, but dropping it would let a broader module grant it
+    NoMethods {
+        module: String,
+    },
+    /// Both methods have different constraints, which don't combine into one Pact contract
+    ConflictingConstraints {
+        module: String,
+        method: String,
+    },
+    /// A combined grant which the deny entries needed for what is not granted would also deny
+    ConflictingDeny {
+        module: String,
+        method: Option<String>,
+    },
+}
+
+impl Display for CombineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyModules => write!(f, "combined TRNNut has too many modules"),
+            Self::TooManyMethods { module } => {
+                write!(f, "combined module {module} has too many methods")
+            }
+            Self::NoMethods { module } => write!(f, "combined module {module} has no methods"),
+            Self::ConflictingConstraints { module, method } => {
+                write!(f, "method {module}::{method} has conflicting constraints")
+            }
+            Self::ConflictingDeny {
+                module,
+                method: None,
+            } => write!(f, "module {module} would be denied by a broader deny entry"),
+            Self::ConflictingDeny {
+                module,
+                method: Some(method),
+            } => write!(
+                f,
+                "method {module}::{method} would be denied by a broader deny entry"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Combine {
+    Intersect,
+    Union,
+}
+
+impl Combine {
+    /// Intersections keep the longer cooldown, unions the shorter
+    fn block_cooldown(self, a: Option<u32>, b: Option<u32>) -> Option<u32> {
+        match self {
+            Self::Intersect => a.max(b),
+            Self::Union => a.zip(b).map(|(a, b)| a.min(b)),
+        }
+    }
+}
+
+/// Returns whether two methods grant the same, regardless of their names
+fn same_method_grant(a: &Method, b: &Method) -> bool {
+    a.block_cooldown == b.block_cooldown && a.constraints == b.constraints && a.deny == b.deny
+}
+
+/// Returns whether two modules grant the same, regardless of their names
+fn same_module_grant(a: &Module, b: &Module) -> bool {
+    a.block_cooldown == b.block_cooldown && a.methods == b.methods && a.deny == b.deny
+}
+
+/// Drops the combined entries which a broader entry already grants identically
+///
+/// `grants` holds the combined entry, if any, for each of `names`. Also returns
+/// the indices of the names which grant nothing, but would fall back to a broader
+/// entry.
+fn prune<T: Clone>(
+    names: &[String],
+    grants: &[Option<T>],
+    entry_name: impl Fn(&T) -> &str,
+    same_grant: impl Fn(&T, &T) -> bool,
+) -> (Vec<T>, Vec<usize>) {
+    let mut entries: Vec<T> = grants.iter().flatten().cloned().collect();
+
+    let mut i = 0;
+    while i < entries.len() {
+        let current = &entries[i];
+        let shadowed: Vec<&String> = names
+            .iter()
+            .filter(|name| {
+                most_specific(&entries, name, &entry_name)
+                    .is_some_and(|resolved| core::ptr::eq(resolved, current))
+            })
+            .collect();
+        let entry = entries.remove(i);
+        let redundant = shadowed.iter().all(|name| {
+            most_specific(&entries, name, &entry_name)
+                .is_some_and(|broader| same_grant(&entry, broader))
+        });
+        if !redundant {
+            entries.insert(i, entry);
+            i += 1;
+        }
+    }
+
+    let fallbacks = grants
+        .iter()
+        .enumerate()
+        .filter(|(i, grant)| {
+            grant.is_none() && most_specific(&entries, &names[*i], &entry_name).is_some()
+        })
+        .map(|(i, _)| i)
+        .collect();
+    (entries, fallbacks)
+}
+
+/// Returns the index of the first name which the combined entries resolve
+/// differently from its intended grant
+fn first_mismatch<'r, T: 'r>(
+    names: &[String],
+    grants: &[Option<T>],
+    resolve: impl Fn(&str) -> Option<&'r T>,
+    same_grant: impl Fn(&T, &T) -> bool,
+) -> Option<usize> {
+    names
+        .iter()
+        .zip(grants)
+        .position(|(name, grant)| match (grant, resolve(name)) {
+            (Some(grant), Some(resolved)) => !same_grant(grant, resolved),
+            (None, None) => false,
+            _ => true,
+        })
+}
+
+/// Returns constraints holding when both, for an intersection, or either, for a union,
+/// constraints of `a` and `b` hold, if both decompile and the result compiles
+fn combine_constraints(op: Combine, a: &Method, b: &Method) -> Option<Vec<u8>> {
+    let a = Constraint::decompile(&a.get_pact()?).ok()?;
+    let b = Constraint::decompile(&b.get_pact()?).ok()?;
+    let combine = |first: &Constraint, second: &Constraint| match op {
+        Combine::Intersect => first.clone().and(second.clone()),
+        Combine::Union => first.clone().or(second.clone()),
+    };
+    // Pact evaluates left to right, so only the first constraint may be compound
+    combine(&a, &b)
+        .to_bytes()
+        .or_else(|_| combine(&b, &a).to_bytes())
+        .ok()
+}
+
+fn combine_method(
+    op: Combine,
+    module_name: &str,
+    name: &str,
+    a: &Method,
+    b: &Method,
+) -> Result<Method, CombineError> {
+    let a_constraints = a.constraints.as_ref().filter(|c| !c.is_empty());
+    let b_constraints = b.constraints.as_ref().filter(|c| !c.is_empty());
+    let constraints = match (a_constraints, b_constraints) {
+        (None, None) => None,
+        (Some(c), None) | (None, Some(c)) => match op {
+            Combine::Intersect => Some(c.clone()),
+            Combine::Union => None,
+        },
+        (Some(a_constraints), Some(b_constraints)) if a_constraints == b_constraints => {
+            Some(a_constraints.clone())
+        }
+        (Some(_), Some(_)) => Some(combine_constraints(op, a, b).ok_or_else(|| {
+            CombineError::ConflictingConstraints {
+                module: module_name.to_string(),
+                method: name.to_string(),
+            }
+        })?),
+    };
+
+    Ok(Method {
+        name: name.to_string(),
+        block_cooldown: op.block_cooldown(a.block_cooldown, b.block_cooldown),
+        constraints,
+        deny: false,
+    })
+}
+
+/// Combines the methods granted by the module of `a` and `b` matching `name`,
+/// at most one of which may be missing when taking a union
+fn combine_module(
+    op: Combine,
+    name: &str,
+    a: Option<&Module>,
+    b: Option<&Module>,
+) -> Result<Option<Module>, CombineError> {
+    let names = representative_names(
+        a.into_iter()
+            .chain(b)
+            .flat_map(|m| &m.methods)
+            .map(|m| m.name.as_str()),
+    );
+    let mut grants: Vec<Option<Method>> = Vec::default();
+    for method_name in &names {
+        let a_method = a.and_then(|m| m.granted_method(method_name));
+        let b_method = b.and_then(|m| m.granted_method(method_name));
+        let method_name = display_name(method_name);
+        let method = match (a_method, b_method, op) {
+            (Some(a), Some(b), _) => Some(combine_method(op, name, &method_name, a, b)?),
+            (Some(m), None, Combine::Union) | (None, Some(m), Combine::Union) => Some(Method {
+                name: method_name,
+                ..m.clone()
+            }),
+            _ => None,
+        };
+        grants.push(method);
+    }
+
+    let (mut methods, fallbacks) = prune(&names, &grants, |m| m.name.as_str(), same_method_grant);
+    if methods.is_empty() {
+        return Ok(None);
+    }
+    // Methods granted by neither side would otherwise fall back to a broader combined method
+    for i in fallbacks {
+        methods.push(Method::unbounded(&display_name(&names[i])).deny());
+    }
+    if methods.len() > MAX_METHODS {
+        return Err(CombineError::TooManyMethods {
+            module: name.to_string(),
+        });
+    }
+
+    let block_cooldown = match (a, b) {
+        (Some(a), Some(b)) => op.block_cooldown(a.block_cooldown, b.block_cooldown),
+        (Some(m), None) | (None, Some(m)) => m.block_cooldown,
+        (None, None) => None,
+    };
+    let mut module = Module::unbounded(name).methods(methods);
+    module.block_cooldown = block_cooldown;
+    if let Some(i) = first_mismatch(
+        &names,
+        &grants,
+        |method| module.granted_method(method),
+        same_method_grant,
+    ) {
+        return Err(CombineError::ConflictingDeny {
+            module: name.to_string(),
+            method: Some(display_name(&names[i])),
+        });
+    }
+    Ok(Some(module))
+}
+
+/// Combines the permissions of `a` and `b`, patterns and deny entries are
+/// resolved with the same rules as `TRNNutV0::validate_module`
+pub(crate) fn combine_modules(
+    op: Combine,
+    a: &[Module],
+    b: &[Module],
+) -> Result<Vec<Module>, CombineError> {
+    let names = representative_names(a.iter().chain(b).map(|m| m.name.as_str()));
+    let mut grants: Vec<Option<Module>> = Vec::default();
+    let mut emptied: Vec<bool> = Vec::default();
+    for name in &names {
+        let a_module = granted_module(a, name);
+        let b_module = granted_module(b, name);
+        let module = match (a_module, b_module, op) {
+            (Some(_), Some(_), _)
+            | (Some(_), None, Combine::Union)
+            | (None, Some(_), Combine::Union) => {
+                let module = combine_module(op, &display_name(name), a_module, b_module)?;
+                emptied.push(module.is_none());
+                module
+            }
+            _ => {
+                emptied.push(false);
+                None
+            }
+        };
+        grants.push(module);
+    }
+
+    let (mut modules, fallbacks) = prune(&names, &grants, |m| m.name.as_str(), same_module_grant);
+    for i in fallbacks {
+        // An emptied module would otherwise fall back to a broader combined module
+        if emptied[i] {
+            return Err(CombineError::NoMethods {
+                module: display_name(&names[i]),
+            });
+        }
+        modules.push(Module::unbounded(&display_name(&names[i])).deny());
+    }
+    if let Some(i) = first_mismatch(
+        &names,
+        &grants,
+        |module| granted_module(&modules, module),
+        same_module_grant,
+    ) {
+        return Err(CombineError::ConflictingDeny {
+            module: display_name(&names[i]),
+            method: None,
+        });
+    }
+
+    if modules.len() > MAX_MODULES {
+        return Err(CombineError::TooManyModules);
+    }
+    Ok(modules)
+}
@@ -0,0 +1,879 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Constraint
+//!
+//! Method constraint expressions of TRNNut and their Pact contracts
+//!
+//! Pact evaluates comparisons left to right, with optional conjunctions
+//! between them and an implicit AND where there are none. A `Constraint`
+//! compiles when each operand of `All` is such a chain, a comparison or an
+//! `All`/`Any` whose first operand is a chain and every other a comparison,
+//! e.g. `arg0 == 1 && (arg1 == 2 || arg1 == 3)` but not
+//! `arg0 == 1 || (arg1 == 2 && arg1 == 3)`.
+//!
+//! Constraints are built with `arg`, e.g.
+//! `arg(0).le(1000).and(arg(1).eq("AUD"))`, and attached with `Method::constrain`.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::String,
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use pact::{
+    interpreter::{Comparator, Conjunction, OpCode, OpComp, OpConj, OpLoad},
+    types::{Contract as PactContract, DataTable, Numeric, PactType, StringLike},
+};
+
+use crate::method::MAX_CONSTRAINTS;
+
+/// Pact packs both operand indices of a comparison into one byte
+const MAX_INDEX: u8 = 0x0f;
+/// Pact encodes the length of a data table value in one byte
+const MAX_VALUE_LEN: usize = 255;
+
+/// Error which may occur while compiling or decompiling a constraint
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConstraintError {
+    /// Only arguments 0 to 15 can be compared
+    ArgIndexOutOfRange(u8),
+    /// The data table can hold at most 16 values
+    TooManyValues,
+    /// A string or list value is longer than 255
+    ValueTooLong,
+    /// The comparison can't apply to the value, e.g. `arg0 < "AUD"`
+    TypeMismatch,
+    /// The constraint is not a sequence Pact can evaluate, see the module docs
+    Unsupported,
+    /// The bytecode is not a sequence of comparisons and AND/OR conjunctions
+    InvalidBytecode,
+    /// The encoded contract is longer than a method's constraints may be
+    TooLong,
+}
+
+impl Display for ConstraintError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ArgIndexOutOfRange(index) => {
+                write!(f, "argument {index} is out of range, at most {MAX_INDEX}")
+            }
+            Self::TooManyValues => write!(f, "constraints compare too many values"),
+            Self::ValueTooLong => write!(f, "a constraint value is too long"),
+            Self::TypeMismatch => write!(f, "a comparison does not apply to its value"),
+            Self::Unsupported => write!(f, "constraints can't be evaluated left to right"),
+            Self::InvalidBytecode => write!(f, "constraints bytecode can't be decompiled"),
+            Self::TooLong => write!(f, "constraints are longer than {MAX_CONSTRAINTS} bytes"),
+        }
+    }
+}
+
+/// A comparison operator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    In,
+    NotIn,
+}
+
+impl CompareOp {
+    /// Returns the Pact comparison and whether it is inverted
+    fn to_pact(self) -> (OpComp, bool) {
+        match self {
+            Self::Eq => (OpComp::EQ, false),
+            Self::Ne => (OpComp::EQ, true),
+            Self::Gt => (OpComp::GT, false),
+            Self::Le => (OpComp::GT, true),
+            Self::Ge => (OpComp::GTE, false),
+            Self::Lt => (OpComp::GTE, true),
+            Self::In => (OpComp::IN, false),
+            Self::NotIn => (OpComp::IN, true),
+        }
+    }
+
+    fn from_pact(op: OpComp, invert: bool) -> Self {
+        match (op, invert) {
+            (OpComp::EQ, false) => Self::Eq,
+            (OpComp::EQ, true) => Self::Ne,
+            (OpComp::GT, false) => Self::Gt,
+            (OpComp::GT, true) => Self::Le,
+            (OpComp::GTE, false) => Self::Ge,
+            (OpComp::GTE, true) => Self::Lt,
+            (OpComp::IN, false) => Self::In,
+            (OpComp::IN, true) => Self::NotIn,
+        }
+    }
+}
+
+impl Display for CompareOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::In => "in",
+            Self::NotIn => "not in",
+        };
+        write!(f, "{op}")
+    }
+}
+
+/// The right hand side of a comparison
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    /// Another argument of the method call
+    Arg(u8),
+    /// A value stored in the data table
+    Value(PactType),
+}
+
+/// A comparison of a method call argument
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub arg: u8,
+    pub op: CompareOp,
+    pub rhs: Operand,
+}
+
+/// A value a method call argument may be compared with
+pub trait IntoValue {
+    fn into_value(self) -> PactType;
+}
+
+impl IntoValue for PactType {
+    fn into_value(self) -> PactType {
+        self
+    }
+}
+
+impl IntoValue for u64 {
+    fn into_value(self) -> PactType {
+        PactType::Numeric(Numeric(self))
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> PactType {
+        PactType::StringLike(StringLike(self.as_bytes().to_vec()))
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> PactType {
+        PactType::StringLike(StringLike(self.into_bytes()))
+    }
+}
+
+impl IntoValue for &[u8] {
+    fn into_value(self) -> PactType {
+        PactType::StringLike(StringLike(self.to_vec()))
+    }
+}
+
+impl IntoValue for Vec<u8> {
+    fn into_value(self) -> PactType {
+        PactType::StringLike(StringLike(self))
+    }
+}
+
+impl<T: IntoValue> From<T> for Operand {
+    fn from(value: T) -> Self {
+        Self::Value(value.into_value())
+    }
+}
+
+impl From<Arg> for Operand {
+    fn from(arg: Arg) -> Self {
+        Self::Arg(arg.0)
+    }
+}
+
+/// A method call argument, by index, to build constraints on
+#[derive(Clone, Copy, Debug)]
+pub struct Arg(pub u8);
+
+/// Returns the method call argument at `index`
+pub fn arg(index: u8) -> Arg {
+    Arg(index)
+}
+
+impl Arg {
+    fn compare(self, op: CompareOp, rhs: Operand) -> Constraint {
+        Constraint::Compare(Comparison {
+            arg: self.0,
+            op,
+            rhs,
+        })
+    }
+
+    pub fn eq(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Eq, rhs.into())
+    }
+
+    pub fn ne(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Ne, rhs.into())
+    }
+
+    pub fn gt(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Gt, rhs.into())
+    }
+
+    pub fn ge(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Ge, rhs.into())
+    }
+
+    pub fn lt(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Lt, rhs.into())
+    }
+
+    pub fn le(self, rhs: impl Into<Operand>) -> Constraint {
+        self.compare(CompareOp::Le, rhs.into())
+    }
+
+    /// Holds when the argument is one of `values`
+    pub fn is_in<T: IntoValue>(self, values: impl IntoIterator<Item = T>) -> Constraint {
+        let values = values.into_iter().map(IntoValue::into_value).collect();
+        self.compare(CompareOp::In, Operand::Value(PactType::List(values)))
+    }
+
+    /// Holds when the argument is none of `values`
+    pub fn not_in<T: IntoValue>(self, values: impl IntoIterator<Item = T>) -> Constraint {
+        let values = values.into_iter().map(IntoValue::into_value).collect();
+        self.compare(CompareOp::NotIn, Operand::Value(PactType::List(values)))
+    }
+}
+
+/// A constraint on the arguments of a method call
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    Compare(Comparison),
+    /// Holds when every constraint holds
+    All(Vec<Constraint>),
+    /// Holds when any constraint holds
+    Any(Vec<Constraint>),
+}
+
+impl Constraint {
+    /// Returns the Pact contract evaluating the constraint
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConstraintError` if the constraint can't be expressed in Pact
+    pub fn compile(&self) -> Result<PactContract, ConstraintError> {
+        let mut compiler = Compiler::default();
+        match self {
+            Self::All(constraints) => {
+                for constraint in constraints {
+                    compiler.chain(constraint)?;
+                }
+            }
+            constraint => compiler.chain(constraint)?,
+        }
+        Ok(PactContract {
+            data_table: DataTable::new(compiler.data_table),
+            bytecode: compiler.bytecode,
+        })
+    }
+
+    /// Returns the encoded Pact contract evaluating the constraint, for `Method::constraints`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConstraintError` if the constraint can't be expressed in Pact,
+    /// or `ConstraintError::TooLong` if the contract doesn't fit a method
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ConstraintError> {
+        let mut constraints = Vec::<u8>::default();
+        self.compile()?.encode(&mut constraints);
+        if constraints.len() > MAX_CONSTRAINTS {
+            return Err(ConstraintError::TooLong);
+        }
+        Ok(constraints)
+    }
+
+    /// Returns the constraint a Pact contract evaluates
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConstraintError::InvalidBytecode` if the contract uses opcodes
+    /// with no `Constraint` equivalent, e.g. XOR, or is malformed
+    pub fn decompile(pact: &PactContract) -> Result<Self, ConstraintError> {
+        let data_table = pact.data_table.as_ref();
+        let bytecode = &pact.bytecode;
+        let mut terms: Vec<Constraint> = Vec::default();
+        let mut conjunction: Option<OpConj> = None;
+        let mut i = 0;
+        while i < bytecode.len() {
+            match OpCode::try_from(bytecode[i]).map_err(|_| ConstraintError::InvalidBytecode)? {
+                OpCode::COMP(comparator) => {
+                    let indices = *bytecode
+                        .get(i + 1)
+                        .ok_or(ConstraintError::InvalidBytecode)?;
+                    let rhs_index = indices & MAX_INDEX;
+                    let rhs = if comparator.load == OpLoad::INPUT_VS_INPUT {
+                        Operand::Arg(rhs_index)
+                    } else {
+                        Operand::Value(
+                            data_table
+                                .get(usize::from(rhs_index))
+                                .ok_or(ConstraintError::InvalidBytecode)?
+                                .clone(),
+                        )
+                    };
+                    let comparison = Self::Compare(Comparison {
+                        arg: indices >> 4,
+                        op: CompareOp::from_pact(comparator.op, comparator.invert),
+                        rhs,
+                    });
+                    match (conjunction.take(), terms.pop()) {
+                        (Some(OpConj::AND), Some(term)) => terms.push(term.and(comparison)),
+                        (Some(OpConj::OR), Some(term)) => terms.push(term.or(comparison)),
+                        (None, term) => {
+                            terms.extend(term);
+                            terms.push(comparison);
+                        }
+                        _ => return Err(ConstraintError::InvalidBytecode),
+                    }
+                    i += 2;
+                }
+                OpCode::CONJ(Conjunction {
+                    op: op @ (OpConj::AND | OpConj::OR),
+                    invert: false,
+                }) if conjunction.is_none() && !terms.is_empty() => {
+                    conjunction = Some(op);
+                    i += 1;
+                }
+                OpCode::CONJ(_) => return Err(ConstraintError::InvalidBytecode),
+            }
+        }
+        if conjunction.is_some() {
+            return Err(ConstraintError::InvalidBytecode);
+        }
+        match terms.len() {
+            0 => Ok(Self::All(Vec::default())),
+            1 => Ok(terms.remove(0)),
+            _ => Ok(terms.into_iter().fold(Self::All(Vec::default()), Self::and)),
+        }
+    }
+
+    /// Returns a constraint holding when both `self` and `other` hold
+    #[must_use]
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All(mut constraints), Self::All(others)) => {
+                constraints.extend(others);
+                Self::All(constraints)
+            }
+            (Self::All(mut constraints), other) => {
+                constraints.push(other);
+                Self::All(constraints)
+            }
+            (constraint, Self::All(mut others)) => {
+                others.insert(0, constraint);
+                Self::All(others)
+            }
+            (constraint, other) => Self::All(Vec::from([constraint, other])),
+        }
+    }
+
+    /// Returns a constraint holding when either `self` or `other` holds
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Any(mut constraints), Self::Any(others)) => {
+                constraints.extend(others);
+                Self::Any(constraints)
+            }
+            (Self::Any(mut constraints), other) => {
+                constraints.push(other);
+                Self::Any(constraints)
+            }
+            (constraint, Self::Any(mut others)) => {
+                others.insert(0, constraint);
+                Self::Any(others)
+            }
+            (constraint, other) => Self::Any(Vec::from([constraint, other])),
+        }
+    }
+}
+
+/// Returns a listing of the comparisons and conjunctions of a Pact contract,
+/// for contracts `Constraint::decompile` can't read
+///
+/// Conjunctions are written as `AND`, `OR`, `XOR`, `NAND`, `NOR` or `XNOR`, and
+/// consecutive comparisons, which must each hold, are separated with `;`.
+pub fn disassemble(pact: &PactContract) -> String {
+    let data_table = pact.data_table.as_ref();
+    let bytecode = &pact.bytecode;
+    let mut listing = String::default();
+    let mut after_comparison = false;
+    let mut i = 0;
+    while i < bytecode.len() {
+        let opcode = bytecode[i];
+        let (text, len) = match (OpCode::try_from(opcode), bytecode.get(i + 1)) {
+            (Ok(OpCode::COMP(comparator)), Some(indices)) => {
+                let rhs_index = indices & MAX_INDEX;
+                let rhs = if comparator.load == OpLoad::INPUT_VS_INPUT {
+                    alloc::format!("arg{rhs_index}")
+                } else if let Some(value) = data_table.get(usize::from(rhs_index)) {
+                    let mut rhs = String::default();
+                    let _ = write_value(&mut rhs, value);
+                    rhs
+                } else {
+                    alloc::format!("<missing value {rhs_index}>")
+                };
+                let op = CompareOp::from_pact(comparator.op, comparator.invert);
+                (alloc::format!("arg{} {op} {rhs}", indices >> 4), 2)
+            }
+            (Ok(OpCode::CONJ(conjunction)), _) => {
+                let op = match (conjunction.op, conjunction.invert) {
+                    (OpConj::AND, false) => "AND",
+                    (OpConj::OR, false) => "OR",
+                    (OpConj::XOR, false) => "XOR",
+                    (OpConj::AND, true) => "NAND",
+                    (OpConj::OR, true) => "NOR",
+                    (OpConj::XOR, true) => "XNOR",
+                };
+                (op.into(), 1)
+            }
+            (Ok(OpCode::COMP(_)), None) => ("<missing indices>".into(), 1),
+            (Err(_), _) => (alloc::format!("<invalid opcode {opcode:#04x}>"), 1),
+        };
+        let is_comparison = len == 2;
+        if !listing.is_empty() {
+            listing.push_str(if after_comparison && is_comparison {
+                "; "
+            } else {
+                " "
+            });
+        }
+        listing.push_str(&text);
+        after_comparison = is_comparison;
+        i += len;
+    }
+    listing
+}
+
+#[derive(Default)]
+struct Compiler {
+    data_table: Vec<PactType>,
+    bytecode: Vec<u8>,
+}
+
+impl Compiler {
+    /// Returns the data table index of `value`, adding it if new
+    fn value_index(&mut self, value: &PactType) -> Result<u8, ConstraintError> {
+        let index = self
+            .data_table
+            .iter()
+            .position(|v| v == value)
+            .unwrap_or_else(|| {
+                self.data_table.push(value.clone());
+                self.data_table.len() - 1
+            });
+        u8::try_from(index)
+            .ok()
+            .filter(|index| *index <= MAX_INDEX)
+            .ok_or(ConstraintError::TooManyValues)
+    }
+
+    fn compare(&mut self, comparison: &Comparison) -> Result<(), ConstraintError> {
+        let arg_index = |index: u8| {
+            if index > MAX_INDEX {
+                Err(ConstraintError::ArgIndexOutOfRange(index))
+            } else {
+                Ok(index)
+            }
+        };
+        let (op, invert) = comparison.op.to_pact();
+        let (load, rhs_index) = match &comparison.rhs {
+            Operand::Arg(index) => (OpLoad::INPUT_VS_INPUT, arg_index(*index)?),
+            Operand::Value(value) => {
+                check_value(op, value)?;
+                (OpLoad::INPUT_VS_USER, self.value_index(value)?)
+            }
+        };
+        let mut comparator = Comparator::new(op).loads(load);
+        if invert {
+            comparator = comparator.invert();
+        }
+        self.bytecode.push(OpCode::COMP(comparator).into());
+        self.bytecode
+            .push(arg_index(comparison.arg)? << 4 | rhs_index);
+        Ok(())
+    }
+
+    /// Compiles a comparison, or an `All`/`Any` evaluated left to right
+    fn chain(&mut self, constraint: &Constraint) -> Result<(), ConstraintError> {
+        let (op, constraints) = match constraint {
+            Constraint::Compare(comparison) => return self.compare(comparison),
+            Constraint::All(constraints) => (OpConj::AND, constraints),
+            Constraint::Any(constraints) => (OpConj::OR, constraints),
+        };
+        let Some((first, rest)) = constraints.split_first() else {
+            return Err(ConstraintError::Unsupported);
+        };
+        self.chain(first)?;
+        for constraint in rest {
+            let Constraint::Compare(comparison) = constraint else {
+                return Err(ConstraintError::Unsupported);
+            };
+            self.bytecode
+                .push(OpCode::CONJ(Conjunction::new(op)).into());
+            self.compare(comparison)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks `value` has a type the comparison applies to and fits the data table
+fn check_value(op: OpComp, value: &PactType) -> Result<(), ConstraintError> {
+    match (op, value) {
+        (OpComp::EQ | OpComp::GT | OpComp::GTE, PactType::Numeric(_)) => Ok(()),
+        (OpComp::EQ, PactType::StringLike(StringLike(bytes))) => {
+            if bytes.len() > MAX_VALUE_LEN {
+                Err(ConstraintError::ValueTooLong)
+            } else {
+                Ok(())
+            }
+        }
+        (OpComp::IN, PactType::List(items)) => {
+            if items.len() > MAX_VALUE_LEN {
+                return Err(ConstraintError::ValueTooLong);
+            }
+            for item in items {
+                check_value(OpComp::EQ, item)?;
+            }
+            Ok(())
+        }
+        _ => Err(ConstraintError::TypeMismatch),
+    }
+}
+
+fn write_value(f: &mut impl fmt::Write, value: &PactType) -> fmt::Result {
+    match value {
+        PactType::Numeric(Numeric(number)) => write!(f, "{number}"),
+        PactType::StringLike(StringLike(bytes)) => {
+            if let Ok(string) = core::str::from_utf8(bytes) {
+                return write!(f, "{string:?}");
+            }
+            write!(f, "0x")?;
+            for byte in bytes {
+                write!(f, "{byte:02x}")?;
+            }
+            Ok(())
+        }
+        PactType::List(items) => {
+            write!(f, "[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(f, item)?;
+            }
+            write!(f, "]")
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "arg{} {} ", self.arg, self.op)?;
+        match &self.rhs {
+            Operand::Arg(index) => write!(f, "arg{index}"),
+            Operand::Value(value) => write_value(f, value),
+        }
+    }
+}
+
+/// Renders as in the policy language, e.g. `arg0 <= 1000 && arg1 == "AUD"`
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (separator, constraints) = match self {
+            Self::Compare(comparison) => return write!(f, "{comparison}"),
+            Self::All(constraints) => (" && ", constraints),
+            Self::Any(constraints) => (" || ", constraints),
+        };
+        if constraints.is_empty() {
+            return write!(f, "{}", matches!(self, Self::All(_)));
+        }
+        for (i, constraint) in constraints.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{separator}")?;
+            }
+            // AND binds tighter than OR
+            let nested = !matches!(
+                (self, constraint),
+                (_, Self::Compare(_)) | (Self::Any(_), Self::All(_))
+            );
+            if nested {
+                write!(f, "({constraint})")?;
+            } else {
+                write!(f, "{constraint}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Comparison> for Constraint {
+    fn from(comparison: Comparison) -> Self {
+        Self::Compare(comparison)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        arg, disassemble, CompareOp, Comparison, Constraint, ConstraintError, Operand, MAX_VALUE_LEN,
+    };
+    use pact::{
+        interpreter::{interpret, Comparator, Conjunction, OpCode, OpComp, OpConj},
+        types::{Contract as PactContract, DataTable, Numeric, PactType, StringLike},
+    };
+
+    fn compare(arg: u8, op: CompareOp, value: PactType) -> Constraint {
+        Constraint::Compare(Comparison {
+            arg,
+            op,
+            rhs: Operand::Value(value),
+        })
+    }
+
+    fn numeric(number: u64) -> PactType {
+        PactType::Numeric(Numeric(number))
+    }
+
+    fn string(string: &str) -> PactType {
+        PactType::StringLike(StringLike(string.as_bytes().to_vec()))
+    }
+
+    #[test]
+    fn it_compiles_comparisons() {
+        let constraint = compare(0, CompareOp::Le, numeric(1000))
+            .and(compare(1, CompareOp::Eq, string("AUD")))
+            .and(compare(2, CompareOp::Ne, numeric(1000)));
+
+        assert_eq!(
+            constraint.compile(),
+            Ok(PactContract {
+                data_table: DataTable::new(vec![numeric(1000), string("AUD")]),
+                bytecode: vec![
+                    OpCode::COMP(Comparator::new(OpComp::GT).invert()).into(),
+                    0x00,
+                    OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                    0x11,
+                    OpCode::COMP(Comparator::new(OpComp::EQ).invert()).into(),
+                    0x20,
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn it_builds_constraints() {
+        assert_eq!(
+            arg(0).le(1000).and(arg(1).eq("AUD")),
+            compare(0, CompareOp::Le, numeric(1000)).and(compare(1, CompareOp::Eq, string("AUD")))
+        );
+        assert_eq!(
+            arg(2).ne(arg(3)).or(arg(4).is_in(["a", "b"])),
+            Constraint::Compare(Comparison {
+                arg: 2,
+                op: CompareOp::Ne,
+                rhs: Operand::Arg(3),
+            })
+            .or(compare(
+                4,
+                CompareOp::In,
+                PactType::List(vec![string("a"), string("b")])
+            ))
+        );
+        assert_eq!(
+            arg(0).not_in([1, 2]),
+            compare(
+                0,
+                CompareOp::NotIn,
+                PactType::List(vec![numeric(1), numeric(2)])
+            )
+        );
+        assert_eq!(
+            arg(0).gt(1).and(arg(0).ge(2)).and(arg(0).lt(3)),
+            Constraint::All(vec![
+                compare(0, CompareOp::Gt, numeric(1)),
+                compare(0, CompareOp::Ge, numeric(2)),
+                compare(0, CompareOp::Lt, numeric(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_encodes_up_to_max_constraints_bytes() {
+        assert_eq!(
+            arg(0).eq("a".repeat(251)).to_bytes().map(|c| c.len()),
+            Ok(257)
+        );
+        let long = (b'a'..=b'p')
+            .map(|c| arg(0).ne(String::from(char::from(c)).repeat(MAX_VALUE_LEN)))
+            .reduce(Constraint::and)
+            .unwrap();
+        assert_eq!(long.to_bytes(), Err(ConstraintError::TooLong));
+    }
+
+    #[test]
+    fn it_compiles_chains() {
+        let constraint = compare(0, CompareOp::Eq, numeric(1))
+            .and(compare(1, CompareOp::Eq, numeric(2)).or(compare(1, CompareOp::Eq, numeric(3))));
+        let pact = constraint.compile().unwrap();
+
+        assert_eq!(
+            pact.bytecode,
+            vec![
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x00,
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x11,
+                OpCode::CONJ(Conjunction::new(OpConj::OR)).into(),
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x12,
+            ]
+        );
+        for (args, expected) in [
+            ([numeric(1), numeric(2)], Ok(true)),
+            ([numeric(1), numeric(3)], Ok(true)),
+            ([numeric(1), numeric(4)], Ok(false)),
+            ([numeric(0), numeric(2)], Ok(false)),
+        ] {
+            assert_eq!(
+                interpret(&args, pact.data_table.as_ref(), &pact.bytecode).map_err(|_| ()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_what_pact_cannot_evaluate() {
+        let unsupported =
+            compare(0, CompareOp::Eq, numeric(1)).or(
+                compare(1, CompareOp::Eq, numeric(2)).and(compare(1, CompareOp::Eq, numeric(3)))
+            );
+        assert_eq!(unsupported.compile(), Err(ConstraintError::Unsupported));
+
+        assert_eq!(
+            compare(16, CompareOp::Eq, numeric(1)).compile(),
+            Err(ConstraintError::ArgIndexOutOfRange(16))
+        );
+        assert_eq!(
+            compare(0, CompareOp::Lt, string("AUD")).compile(),
+            Err(ConstraintError::TypeMismatch)
+        );
+        assert_eq!(
+            compare(0, CompareOp::In, numeric(1)).compile(),
+            Err(ConstraintError::TypeMismatch)
+        );
+        assert_eq!(
+            compare(0, CompareOp::Eq, string(&"a".repeat(256))).compile(),
+            Err(ConstraintError::ValueTooLong)
+        );
+        let too_many = (0..17_u64).fold(Constraint::All(vec![]), |c, n| {
+            c.and(compare(0, CompareOp::Ne, numeric(n)))
+        });
+        assert_eq!(too_many.compile(), Err(ConstraintError::TooManyValues));
+    }
+
+    #[test]
+    fn it_decompiles_what_it_compiles() {
+        let constraints = [
+            compare(0, CompareOp::Le, numeric(1000)).and(compare(1, CompareOp::Eq, string("AUD"))),
+            compare(0, CompareOp::Eq, numeric(1))
+                .or(compare(0, CompareOp::Gt, numeric(5)))
+                .and(compare(
+                    1,
+                    CompareOp::NotIn,
+                    PactType::List(vec![string("a")]),
+                )),
+            compare(0, CompareOp::Lt, numeric(1))
+                .and(compare(1, CompareOp::Ge, numeric(2)))
+                .or(Constraint::Compare(Comparison {
+                    arg: 2,
+                    op: CompareOp::Eq,
+                    rhs: Operand::Arg(3),
+                })),
+        ];
+        for constraint in constraints {
+            let pact = constraint.compile().unwrap();
+            assert_eq!(Constraint::decompile(&pact), Ok(constraint));
+        }
+    }
+
+    #[test]
+    fn it_does_not_decompile_xor() {
+        let pact = PactContract {
+            data_table: DataTable::new(vec![numeric(1)]),
+            bytecode: vec![
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x00,
+                OpCode::CONJ(Conjunction::new(OpConj::XOR)).into(),
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x10,
+            ],
+        };
+
+        assert_eq!(
+            Constraint::decompile(&pact),
+            Err(ConstraintError::InvalidBytecode)
+        );
+    }
+
+    #[test]
+    fn it_disassembles_what_it_cannot_decompile() {
+        let pact = PactContract {
+            data_table: DataTable::new(vec![numeric(1), string("AUD")]),
+            bytecode: vec![
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x00,
+                OpCode::CONJ(Conjunction::new(OpConj::XOR)).into(),
+                OpCode::COMP(Comparator::new(OpComp::GT).invert()).into(),
+                0x10,
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x21,
+                OpCode::CONJ(Conjunction::new(OpConj::OR).invert()).into(),
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x35,
+            ],
+        };
+
+        assert_eq!(
+            disassemble(&pact),
+            r#"arg0 == 1 XOR arg1 <= 1; arg2 == "AUD" NOR arg3 == <missing value 5>"#
+        );
+    }
+
+    #[test]
+    fn it_displays_with_precedence() {
+        let constraint = compare(0, CompareOp::Eq, numeric(1))
+            .or(compare(0, CompareOp::Eq, numeric(2)))
+            .and(compare(
+                1,
+                CompareOp::In,
+                PactType::List(vec![string("AUD"), numeric(3)]),
+            ))
+            .or(compare(
+                2,
+                CompareOp::Ne,
+                PactType::StringLike(StringLike(vec![0xff])),
+            ));
+
+        assert_eq!(
+            constraint.to_string(),
+            r#"(arg0 == 1 || arg0 == 2) && arg1 in ["AUD", 3] || arg2 != 0xff"#
+        );
+    }
+}
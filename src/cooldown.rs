@@ -0,0 +1,99 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Cooldown
+//!
+//! Block cooldown state tracking of TRNNut for use in TRN
+//!
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Tracks the block at which a holder last used a module or method
+///
+/// Entries are keyed by the runtime module and method names being called,
+/// not by the (possibly wildcard) names of the matching permission entries.
+pub trait CooldownStore {
+    /// Returns the block at which `holder` last used any method of `module`
+    fn last_module_use(&self, holder: &[u8], module: &str) -> Option<u32>;
+
+    /// Returns the block at which `holder` last used `method` of `module`
+    fn last_method_use(&self, holder: &[u8], module: &str, method: &str) -> Option<u32>;
+
+    /// Records that `holder` used `method` of `module` at `block_number`
+    fn record_use(&mut self, holder: &[u8], module: &str, method: &str, block_number: u32);
+}
+
+/// A `CooldownStore` held in memory, for tests and off-chain simulators
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InMemoryCooldownStore {
+    modules: BTreeMap<(Vec<u8>, String), u32>,
+    methods: BTreeMap<(Vec<u8>, String, String), u32>,
+}
+
+impl InMemoryCooldownStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CooldownStore for InMemoryCooldownStore {
+    fn last_module_use(&self, holder: &[u8], module: &str) -> Option<u32> {
+        self.modules
+            .get(&(holder.to_vec(), module.into()))
+            .copied()
+    }
+
+    fn last_method_use(&self, holder: &[u8], module: &str, method: &str) -> Option<u32> {
+        self.methods
+            .get(&(holder.to_vec(), module.into(), method.into()))
+            .copied()
+    }
+
+    fn record_use(&mut self, holder: &[u8], module: &str, method: &str, block_number: u32) {
+        self.modules
+            .insert((holder.to_vec(), module.into()), block_number);
+        self.methods
+            .insert((holder.to_vec(), module.into(), method.into()), block_number);
+    }
+}
+
+/// Returns the number of blocks until `block_cooldown` has elapsed since `last_used`
+pub(crate) fn remaining_blocks(
+    block_cooldown: Option<u32>,
+    last_used: Option<u32>,
+    block_number: u32,
+) -> u32 {
+    match (block_cooldown, last_used) {
+        (Some(cooldown), Some(last_used)) => last_used
+            .saturating_add(cooldown)
+            .saturating_sub(block_number),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{remaining_blocks, CooldownStore, InMemoryCooldownStore};
+
+    #[test]
+    fn it_records_uses() {
+        let mut store = InMemoryCooldownStore::new();
+        store.record_use(b"alice", "Balances", "transfer", 10);
+
+        assert_eq!(store.last_module_use(b"alice", "Balances"), Some(10));
+        assert_eq!(
+            store.last_method_use(b"alice", "Balances", "transfer"),
+            Some(10)
+        );
+        assert_eq!(store.last_method_use(b"alice", "Balances", "burn"), None);
+        assert_eq!(store.last_module_use(b"bob", "Balances"), None);
+    }
+
+    #[test]
+    fn it_computes_remaining_blocks() {
+        assert_eq!(remaining_blocks(None, Some(10), 11), 0);
+        assert_eq!(remaining_blocks(Some(5), None, 11), 0);
+        assert_eq!(remaining_blocks(Some(5), Some(10), 11), 4);
+        assert_eq!(remaining_blocks(Some(5), Some(10), 15), 0);
+        assert_eq!(remaining_blocks(Some(u32::MAX), Some(10), 11), u32::MAX - 11);
+    }
+}
@@ -0,0 +1,516 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Edit
+//!
+//! Editing of TRNNut modules and methods
+//!
+//! Edits address modules and methods by their exact name, patterns included,
+//! and keep names unique. An edit which would leave the TRNNut unencodable,
+//! such as removing its last module or a module's last method, is rejected,
+//! leaving the TRNNut unchanged.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+use codec::Encode;
+use core::convert::TryFrom;
+
+use crate::{
+    method::{Method, MAX_CONSTRAINTS},
+    module::Module,
+    name::{MethodName, ModuleName, NameError, MAX_NAME_BYTES},
+    trnnut::{MAX_METHODS, MAX_MODULES, MAX_TRNNUT_BYTES, VERSION_BYTES},
+};
+
+/// Error which may occur while editing a TRNNut
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EditError {
+    DuplicateModule {
+        module: String,
+    },
+    DuplicateMethod {
+        module: String,
+        method: String,
+    },
+    NoSuchModule {
+        module: String,
+    },
+    NoSuchMethod {
+        module: String,
+        method: String,
+    },
+    /// A module or method name which would not encode intact
+    InvalidName {
+        name: String,
+        error: NameError,
+    },
+    /// Constraints which are not a Pact contract
+    InvalidConstraints {
+        module: String,
+        method: String,
+    },
+    ConstraintsTooLong {
+        module: String,
+        method: String,
+    },
+    NoModules,
+    TooManyModules,
+    NoMethods {
+        module: String,
+    },
+    TooManyMethods {
+        module: String,
+    },
+    PayloadTooLarge {
+        size: usize,
+    },
+    /// Denied modules can only be encoded from TRNNut version 1
+    ModuleDenyUnsupported {
+        module: String,
+    },
+    /// Denied methods can only be encoded from TRNNut version 1
+    MethodDenyUnsupported {
+        module: String,
+        method: String,
+    },
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateModule { module } => write!(f, "module {module} already exists"),
+            Self::DuplicateMethod { module, method } => {
+                write!(f, "method {module}::{method} already exists")
+            }
+            Self::NoSuchModule { module } => write!(f, "module {module} does not exist"),
+            Self::NoSuchMethod { module, method } => {
+                write!(f, "method {module}::{method} does not exist")
+            }
+            Self::InvalidName { name, error } => write!(f, "invalid name {name:?}: {error}"),
+            Self::InvalidConstraints { module, method } => {
+                write!(f, "method {module}::{method} has invalid constraints")
+            }
+            Self::ConstraintsTooLong { module, method } => {
+                write!(
+                    f,
+                    "method {module}::{method} has constraints longer than {MAX_CONSTRAINTS} bytes"
+                )
+            }
+            Self::NoModules => write!(f, "TRNNut would have no modules"),
+            Self::TooManyModules => write!(f, "TRNNut would have too many modules"),
+            Self::NoMethods { module } => write!(f, "module {module} would have no methods"),
+            Self::TooManyMethods { module } => {
+                write!(f, "module {module} would have too many methods")
+            }
+            Self::PayloadTooLarge { size } => write!(f, "TRNNut would be too large: {size} bytes"),
+            Self::ModuleDenyUnsupported { module } => {
+                write!(f, "module {module} is denied, which version 0 can't encode")
+            }
+            Self::MethodDenyUnsupported { module, method } => {
+                write!(
+                    f,
+                    "method {module}::{method} is denied, which version 0 can't encode"
+                )
+            }
+        }
+    }
+}
+
+/// Applies `edit` to a copy of `modules`, which replaces them only if it is within `MAX_TRNNUT_BYTES`
+pub(crate) fn edit_modules<T>(
+    modules: &mut Vec<Module>,
+    edit: impl FnOnce(&mut Vec<Module>) -> Result<T, EditError>,
+) -> Result<T, EditError> {
+    let mut edited = modules.clone();
+    let outcome = edit(&mut edited)?;
+    let size = encoded_len(&edited);
+    if size > MAX_TRNNUT_BYTES {
+        return Err(EditError::PayloadTooLarge { size });
+    }
+    *modules = edited;
+    Ok(outcome)
+}
+
+/// Returns the length of `modules` encoded as version 0, without checking they encode
+fn encoded_len(modules: &[Module]) -> usize {
+    let cooldown_len = |cooldown: Option<u32>| cooldown.map_or(0, |c| c.encoded_size());
+    VERSION_BYTES.len()
+        + 1
+        + modules
+            .iter()
+            .map(|module| {
+                1 + MAX_NAME_BYTES
+                    + cooldown_len(module.block_cooldown)
+                    + module
+                        .methods
+                        .iter()
+                        .map(|m| m.encode().len())
+                        .sum::<usize>()
+            })
+            .sum::<usize>()
+}
+
+fn position_of_module(modules: &[Module], module: &str) -> Result<usize, EditError> {
+    modules
+        .iter()
+        .position(|m| m.name == module)
+        .ok_or_else(|| EditError::NoSuchModule {
+            module: module.to_string(),
+        })
+}
+
+fn position_of_method(module: &Module, method: &str) -> Result<usize, EditError> {
+    module
+        .methods
+        .iter()
+        .position(|m| m.name == method)
+        .ok_or_else(|| EditError::NoSuchMethod {
+            module: module.name.clone(),
+            method: method.to_string(),
+        })
+}
+
+/// Checks the name and constraints of `method` of `module` encode as they are
+fn check_method(module: &str, method: &Method) -> Result<(), EditError> {
+    MethodName::try_from(method.name.as_str()).map_err(|error| EditError::InvalidName {
+        name: method.name.clone(),
+        error,
+    })?;
+    if method.deny {
+        return Err(EditError::MethodDenyUnsupported {
+            module: module.to_string(),
+            method: method.name.clone(),
+        });
+    }
+    check_constraints(module, method)
+}
+
+fn check_constraints(module: &str, method: &Method) -> Result<(), EditError> {
+    let Some(constraints) = method.constraints.as_ref().filter(|c| !c.is_empty()) else {
+        return Ok(());
+    };
+    if constraints.len() > MAX_CONSTRAINTS {
+        return Err(EditError::ConstraintsTooLong {
+            module: module.to_string(),
+            method: method.name.clone(),
+        });
+    }
+    if method.get_pact().is_none() {
+        return Err(EditError::InvalidConstraints {
+            module: module.to_string(),
+            method: method.name.clone(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn add_module(modules: &mut Vec<Module>, module: Module) -> Result<(), EditError> {
+    ModuleName::try_from(module.name.as_str()).map_err(|error| EditError::InvalidName {
+        name: module.name.clone(),
+        error,
+    })?;
+    if modules.iter().any(|m| m.name == module.name) {
+        return Err(EditError::DuplicateModule {
+            module: module.name,
+        });
+    }
+    if module.deny {
+        return Err(EditError::ModuleDenyUnsupported {
+            module: module.name,
+        });
+    }
+    if modules.len() >= MAX_MODULES {
+        return Err(EditError::TooManyModules);
+    }
+    if module.methods.is_empty() {
+        return Err(EditError::NoMethods {
+            module: module.name,
+        });
+    }
+    if module.methods.len() > MAX_METHODS {
+        return Err(EditError::TooManyMethods {
+            module: module.name,
+        });
+    }
+    for (i, method) in module.methods.iter().enumerate() {
+        check_method(&module.name, method)?;
+        if module.methods[..i].iter().any(|m| m.name == method.name) {
+            return Err(EditError::DuplicateMethod {
+                module: module.name.clone(),
+                method: method.name.clone(),
+            });
+        }
+    }
+    modules.push(module);
+    Ok(())
+}
+
+pub(crate) fn remove_module(modules: &mut Vec<Module>, module: &str) -> Result<Module, EditError> {
+    let i = position_of_module(modules, module)?;
+    if modules.len() == 1 {
+        return Err(EditError::NoModules);
+    }
+    Ok(modules.remove(i))
+}
+
+pub(crate) fn upsert_method(
+    modules: &mut [Module],
+    module: &str,
+    method: Method,
+) -> Result<Option<Method>, EditError> {
+    let module = &mut modules[position_of_module(modules, module)?];
+    check_method(&module.name, &method)?;
+    if let Ok(j) = position_of_method(module, &method.name) {
+        return Ok(Some(core::mem::replace(&mut module.methods[j], method)));
+    }
+    if module.methods.len() >= MAX_METHODS {
+        return Err(EditError::TooManyMethods {
+            module: module.name.clone(),
+        });
+    }
+    module.methods.push(method);
+    Ok(None)
+}
+
+pub(crate) fn remove_method(
+    modules: &mut [Module],
+    module: &str,
+    method: &str,
+) -> Result<Method, EditError> {
+    let module = &mut modules[position_of_module(modules, module)?];
+    let j = position_of_method(module, method)?;
+    if module.methods.len() == 1 {
+        return Err(EditError::NoMethods {
+            module: module.name.clone(),
+        });
+    }
+    Ok(module.methods.remove(j))
+}
+
+pub(crate) fn set_cooldown(
+    modules: &mut [Module],
+    module: &str,
+    method: Option<&str>,
+    block_cooldown: Option<u32>,
+) -> Result<(), EditError> {
+    let module = &mut modules[position_of_module(modules, module)?];
+    match method {
+        Some(method) => {
+            let j = position_of_method(module, method)?;
+            module.methods[j].block_cooldown = block_cooldown;
+        }
+        None => module.block_cooldown = block_cooldown,
+    }
+    Ok(())
+}
+
+pub(crate) fn set_constraints(
+    modules: &mut [Module],
+    module: &str,
+    method: &str,
+    constraints: Option<Vec<u8>>,
+) -> Result<(), EditError> {
+    let module = &mut modules[position_of_module(modules, module)?];
+    let j = position_of_method(module, method)?;
+    module.methods[j].constraints = constraints;
+    check_constraints(&module.name, &module.methods[j])
+}
+
+#[cfg(test)]
+mod test {
+    use super::EditError;
+    use crate::{
+        constraint::arg, method::Method, module::Module, tests::make_trnnut, trnnut::MAX_METHODS,
+        TryEncode,
+    };
+
+    #[test]
+    fn it_adds_and_removes_modules() {
+        let mut trnnut = make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer")])]);
+
+        assert_eq!(
+            trnnut.add_module(Module::new("Balances")),
+            Err(EditError::DuplicateModule {
+                module: "Balances".into()
+            })
+        );
+        assert_eq!(
+            trnnut.add_module(
+                Module::new("Nft").methods(vec![Method::new("mint"), Method::new("mint")])
+            ),
+            Err(EditError::DuplicateMethod {
+                module: "Nft".into(),
+                method: "mint".into()
+            })
+        );
+        assert!(trnnut
+            .add_module(Module::unbounded(&"a".repeat(33)))
+            .is_err());
+        assert_eq!(
+            trnnut.add_module(Module::new("Nft")),
+            Err(EditError::NoMethods {
+                module: "Nft".into()
+            })
+        );
+        assert_eq!(
+            trnnut.add_module(Module::new("Nft").deny()),
+            Err(EditError::ModuleDenyUnsupported {
+                module: "Nft".into()
+            })
+        );
+        assert_eq!(
+            trnnut.add_module(Module::new("Nft").methods(vec![Method::new("mint")])),
+            Ok(())
+        );
+        assert_eq!(trnnut.modules.len(), 2);
+
+        assert_eq!(
+            trnnut.remove_module("Balances").map(|m| m.name),
+            Ok("Balances".into())
+        );
+        assert_eq!(
+            trnnut.remove_module("Balances"),
+            Err(EditError::NoSuchModule {
+                module: "Balances".into()
+            })
+        );
+        // The last module is kept, as a TRNNut without modules would not encode
+        assert_eq!(trnnut.remove_module("Nft"), Err(EditError::NoModules));
+        assert_eq!(trnnut.modules.len(), 1);
+        assert!(trnnut.try_encode().is_ok());
+    }
+
+    #[test]
+    fn it_upserts_and_removes_methods() {
+        let mut trnnut = make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer")])]);
+
+        assert_eq!(
+            trnnut.upsert_method("Balances", Method::new("transfer").block_cooldown(5)),
+            Ok(Some(Method::new("transfer")))
+        );
+        assert_eq!(
+            trnnut.upsert_method("Balances", Method::new("burn")),
+            Ok(None)
+        );
+        assert_eq!(
+            trnnut.upsert_method("Staking", Method::new("bond")),
+            Err(EditError::NoSuchModule {
+                module: "Staking".into()
+            })
+        );
+        assert_eq!(
+            trnnut.upsert_method("Balances", Method::new("mint").deny()),
+            Err(EditError::MethodDenyUnsupported {
+                module: "Balances".into(),
+                method: "mint".into()
+            })
+        );
+        assert_eq!(
+            trnnut.get_module("Balances").unwrap().methods,
+            vec![
+                Method::new("transfer").block_cooldown(5),
+                Method::new("burn")
+            ]
+        );
+
+        assert_eq!(
+            trnnut.remove_method("Balances", "burn"),
+            Ok(Method::new("burn"))
+        );
+        assert_eq!(
+            trnnut.remove_method("Balances", "burn"),
+            Err(EditError::NoSuchMethod {
+                module: "Balances".into(),
+                method: "burn".into()
+            })
+        );
+        // The last method is kept, as a module without methods would not encode
+        assert_eq!(
+            trnnut.remove_method("Balances", "transfer"),
+            Err(EditError::NoMethods {
+                module: "Balances".into()
+            })
+        );
+        assert_eq!(trnnut.get_module("Balances").unwrap().methods.len(), 1);
+        assert!(trnnut.try_encode().is_ok());
+    }
+
+    #[test]
+    fn it_sets_cooldowns_and_constraints() {
+        let mut trnnut = make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer")])]);
+        let constraints = Method::new("transfer")
+            .constrain(arg(0).le(1000))
+            .unwrap()
+            .constraints;
+
+        assert_eq!(trnnut.set_cooldown("Balances", None, None), Ok(()));
+        assert_eq!(
+            trnnut.set_cooldown("Balances", Some("transfer"), Some(5)),
+            Ok(())
+        );
+        assert_eq!(
+            trnnut.set_constraints("Balances", "transfer", constraints.clone()),
+            Ok(())
+        );
+        assert_eq!(
+            trnnut.set_constraints("Balances", "transfer", Some(vec![1, 2, 3])),
+            Err(EditError::InvalidConstraints {
+                module: "Balances".into(),
+                method: "transfer".into()
+            })
+        );
+        assert_eq!(
+            trnnut.set_cooldown("Balances", Some("burn"), Some(5)),
+            Err(EditError::NoSuchMethod {
+                module: "Balances".into(),
+                method: "burn".into()
+            })
+        );
+
+        let module = trnnut.get_module("Balances").unwrap();
+        assert_eq!(module.block_cooldown, None);
+        assert_eq!(module.methods[0].block_cooldown, Some(5));
+        assert_eq!(module.methods[0].constraints, constraints);
+    }
+
+    #[test]
+    fn it_enforces_encoding_limits() {
+        let mut trnnut = make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer")])]);
+        for x in 1..MAX_METHODS {
+            trnnut
+                .upsert_method("Balances", Method::new(&x.to_string()))
+                .unwrap();
+        }
+        assert_eq!(
+            trnnut.upsert_method("Balances", Method::new("burn")),
+            Err(EditError::TooManyMethods {
+                module: "Balances".into()
+            })
+        );
+
+        let mut x = 0;
+        let err = loop {
+            let module =
+                Module::new(&format!("Module{x}")).methods(trnnut.modules[0].methods.clone());
+            if let Err(err) = trnnut.add_module(module) {
+                break err;
+            }
+            x += 1;
+        };
+        assert!(matches!(err, EditError::PayloadTooLarge { .. }));
+        // The rejected edit leaves the TRNNut encodable
+        assert_eq!(trnnut.modules.len(), x + 1);
+        assert!(trnnut.try_encode().is_ok());
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Encode
+//!
+//! Fallible encoding of TRNNut types
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::String,
+    vec::Vec,
+};
+use codec::Output;
+
+use crate::{limits::Limits, name::NameError};
+
+/// Error which may occur while encoding a trnnut, module or method
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncodeError {
+    NoModules,
+    TooManyModules,
+    NoMethods { module: String },
+    TooManyMethods { module: String },
+    NameTooLong,
+    /// Version 0 ends names at their first NUL byte
+    NameContainsNul,
+    ConstraintsTooLong,
+    PayloadTooLarge { size: usize },
+    /// Denied modules can only be encoded from TRNNut version 1
+    ModuleDenyUnsupported { module: String },
+    /// Denied methods can only be encoded from TRNNut version 1, see `DENY_MASK`
+    MethodDenyUnsupported { method: String },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoModules => write!(f, "TRNNut has no modules"),
+            Self::TooManyModules => write!(f, "TRNNut has too many modules"),
+            Self::NoMethods { module } => write!(f, "module {module} has no methods"),
+            Self::TooManyMethods { module } => write!(f, "module {module} has too many methods"),
+            Self::NameTooLong => write!(f, "name is longer than 32 bytes"),
+            Self::NameContainsNul => write!(f, "name contains a NUL byte"),
+            Self::ConstraintsTooLong => write!(f, "constraints are too long"),
+            Self::PayloadTooLarge { size } => write!(f, "TRNNut is too large: {size} bytes"),
+            Self::ModuleDenyUnsupported { module } => {
+                write!(f, "module {module} is denied, which this version can't encode")
+            }
+            Self::MethodDenyUnsupported { method } => {
+                write!(f, "method {method} is denied, which this version can't encode")
+            }
+        }
+    }
+}
+
+impl From<NameError> for EncodeError {
+    fn from(err: NameError) -> Self {
+        match err {
+            NameError::TooLong(_) => Self::NameTooLong,
+            NameError::NulByte => Self::NameContainsNul,
+        }
+    }
+}
+
+pub trait TryEncode {
+    /// Encode into `buf` within `limits`, nothing is written if encoding fails
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns an `EncodeError`
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError>;
+
+    /// Encode into `buf` within the default limits, nothing is written if encoding fails
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns an `EncodeError`
+    fn try_encode_to<T: Output + ?Sized>(&self, buf: &mut T) -> Result<(), EncodeError> {
+        self.try_encode_limited_to(buf, Limits::default())
+    }
+
+    /// Encode into a new byte vector within `limits`
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns an `EncodeError`
+    fn try_encode_limited(&self, limits: Limits) -> Result<Vec<u8>, EncodeError> {
+        let mut buf = Vec::<u8>::default();
+        self.try_encode_limited_to(&mut buf, limits)?;
+        Ok(buf)
+    }
+
+    /// Encode into a new byte vector
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns an `EncodeError`
+    fn try_encode(&self) -> Result<Vec<u8>, EncodeError> {
+        self.try_encode_limited(Limits::default())
+    }
+}
@@ -0,0 +1,346 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Explain
+//!
+//! Validation traces of TRNNut for use in TRN
+//!
+//! A trace records how `validate_module` reached its result, which module and
+//! method entries matched and where the constraints failed, for logging and
+//! for showing to end users.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+use pact::{
+    interpreter::{interpret, OpCode, OpLoad},
+    types::PactType,
+};
+
+use crate::{
+    constraint::Constraint,
+    method::Method,
+    module::Module,
+    pattern::{specificity, Specificity},
+    trnnut::{find_deny, find_module},
+    RuntimeDomain, ValidationErr, WILDCARD,
+};
+
+/// How a module or method entry matched a runtime name
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MatchKind {
+    Exact,
+    Pattern,
+    Wildcard,
+}
+
+impl Display for MatchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::Pattern => write!(f, "pattern"),
+            Self::Wildcard => write!(f, "wildcard"),
+        }
+    }
+}
+
+/// The module or method entry which decided a runtime name
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchedEntry {
+    pub name: String,
+    pub kind: MatchKind,
+    /// Whether the entry is a deny entry
+    pub deny: bool,
+}
+
+impl MatchedEntry {
+    fn new(entry_name: &str, runtime_name: &str, deny: bool) -> Self {
+        let kind = match specificity(entry_name, runtime_name) {
+            Some(Specificity::Exact) => MatchKind::Exact,
+            _ if entry_name == WILDCARD => MatchKind::Wildcard,
+            _ => MatchKind::Pattern,
+        };
+        Self {
+            name: entry_name.to_string(),
+            kind,
+            deny,
+        }
+    }
+}
+
+impl Display for MatchedEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let verb = if self.deny { "denied" } else { "granted" };
+        write!(f, "{verb} by {} ({})", self.name, self.kind)
+    }
+}
+
+/// The comparison of the constraints at which validation stopped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConstraintsFailure {
+    /// Byte offset of the opcode in the constraints bytecode
+    pub opcode_index: usize,
+    pub opcode: u8,
+    /// Index of the input argument compared
+    pub arg_index: u8,
+    /// Index of the data table item, or input argument, compared against
+    pub rhs_index: u8,
+    /// Whether the comparison is against an input argument rather than the data table
+    pub rhs_is_arg: bool,
+}
+
+impl Display for ConstraintsFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rhs = if self.rhs_is_arg {
+            "argument"
+        } else {
+            "data table item"
+        };
+        write!(
+            f,
+            "opcode {:#04x} at {}, comparing argument {} with {rhs} {}",
+            self.opcode, self.opcode_index, self.arg_index, self.rhs_index
+        )
+    }
+}
+
+/// Whether the Pact interpreter ran, and how it ended
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PactTrace {
+    /// Validation ended before the constraints, or the method has none
+    NotRun,
+    Passed,
+    /// The constraints are not satisfied, at the first unsatisfied comparison if known
+    Failed(Option<ConstraintsFailure>),
+    /// The constraints could not be interpreted, at the first erroring comparison if known
+    Errored(Option<ConstraintsFailure>),
+}
+
+impl Display for PactTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRun => write!(f, "constraints not run"),
+            Self::Passed => write!(f, "constraints passed"),
+            Self::Failed(Some(failure)) => write!(f, "constraints failed at {failure}"),
+            Self::Failed(None) => write!(f, "constraints failed"),
+            Self::Errored(Some(failure)) => {
+                write!(f, "constraints could not be interpreted at {failure}")
+            }
+            Self::Errored(None) => write!(f, "constraints could not be interpreted"),
+        }
+    }
+}
+
+/// How a runtime module and method were validated, see `TRNNutV0::validate_module_explain`
+#[derive(Debug, Eq, PartialEq)]
+pub struct ValidationTrace {
+    pub module_name: String,
+    pub method_name: String,
+    /// The module entry which decided `module_name`, if any matched
+    pub module: Option<MatchedEntry>,
+    /// The method entry which decided `method_name`, if validation got that far
+    pub method: Option<MatchedEntry>,
+    pub pact: PactTrace,
+    /// The result `validate_module` returns
+    pub result: Result<(), ValidationErr<RuntimeDomain>>,
+}
+
+impl Display for ValidationTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "module {}: ", self.module_name)?;
+        match &self.module {
+            Some(module) => write!(f, "{module}")?,
+            None => write!(f, "no matching entry")?,
+        }
+        if self.module.as_ref().is_some_and(|m| !m.deny) {
+            write!(f, "; method {}: ", self.method_name)?;
+            match &self.method {
+                Some(method) => write!(f, "{method}")?,
+                None => write!(f, "no matching entry")?,
+            }
+        }
+        write!(f, "; {}; ", self.pact)?;
+        match &self.result {
+            Ok(()) => write!(f, "permitted"),
+            Err(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// Returns the comparison at byte offset `i` of `bytecode`
+///
+/// Comparisons are an opcode followed by an index byte, the argument index in
+/// its high nibble and the right hand side index in its low nibble.
+fn comparison_at(bytecode: &[u8], i: usize) -> Option<ConstraintsFailure> {
+    let opcode = *bytecode.get(i)?;
+    let OpCode::COMP(comparator) = OpCode::try_from(opcode).ok()? else {
+        return None;
+    };
+    let indices = *bytecode.get(i + 1)?;
+    Some(ConstraintsFailure {
+        opcode_index: i,
+        opcode,
+        arg_index: indices >> 4,
+        rhs_index: indices & 0x0f,
+        rhs_is_arg: comparator.load == OpLoad::INPUT_VS_INPUT,
+    })
+}
+
+/// Returns the byte offsets of the comparisons of `bytecode` in order, up to any invalid opcode
+fn comparison_offsets(bytecode: &[u8]) -> Vec<usize> {
+    let mut offsets = Vec::default();
+    let mut i = 0;
+    while i < bytecode.len() {
+        match OpCode::try_from(bytecode[i]) {
+            Ok(OpCode::COMP(_)) if i + 1 < bytecode.len() => {
+                offsets.push(i);
+                i += 2;
+            }
+            Ok(OpCode::CONJ(_)) => i += 1,
+            _ => break,
+        }
+    }
+    offsets
+}
+
+/// Locates the first comparison of `bytecode` for which `stops` holds,
+/// by interpreting each comparison on its own
+fn locate_comparison(
+    args: &[PactType],
+    data_table: &[PactType],
+    bytecode: &[u8],
+    stops: impl Fn(&Result<bool, ()>) -> bool,
+) -> Option<ConstraintsFailure> {
+    comparison_offsets(bytecode).into_iter().find_map(|i| {
+        let outcome = interpret(args, data_table, &bytecode[i..i + 2]).map_err(|_| ());
+        if stops(&outcome) {
+            comparison_at(bytecode, i)
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns whether `constraint` holds, given whether each of its comparisons holds
+/// in order by byte offset, and if it doesn't the offset of the comparison deciding
+/// so: the decider of the first term of `All` which fails, or of the last term of
+/// `Any`, when every term fails
+fn decide(
+    constraint: &Constraint,
+    outcomes: &mut impl Iterator<Item = (usize, bool)>,
+) -> (bool, Option<usize>) {
+    match constraint {
+        Constraint::Compare(_) => match outcomes.next() {
+            Some((i, holds)) => (holds, Some(i).filter(|_| !holds)),
+            None => (false, None),
+        },
+        Constraint::All(terms) => {
+            let mut decided = (true, None);
+            for term in terms {
+                let (holds, i) = decide(term, outcomes);
+                if decided.0 && !holds {
+                    decided = (false, i);
+                }
+            }
+            decided
+        }
+        Constraint::Any(terms) => {
+            let mut decided = (false, None);
+            for term in terms {
+                let (holds, i) = decide(term, outcomes);
+                if !decided.0 {
+                    decided = (holds, i);
+                }
+            }
+            decided
+        }
+    }
+}
+
+/// Locates the comparison deciding that `constraint`, decompiled from `bytecode`, fails
+/// Unlike `locate_comparison`, a comparison failing within a term which holds is not
+/// blamed, e.g. `arg1 == 2` of `(arg0 == 1 || arg1 == 2) && arg2 == 3`
+fn locate_deciding_comparison(
+    constraint: &Constraint,
+    args: &[PactType],
+    data_table: &[PactType],
+    bytecode: &[u8],
+) -> Option<ConstraintsFailure> {
+    let mut outcomes = comparison_offsets(bytecode).into_iter().map(|i| {
+        let holds = interpret(args, data_table, &bytecode[i..i + 2]) == Ok(true);
+        (i, holds)
+    });
+    let (_, i) = decide(constraint, &mut outcomes);
+    comparison_at(bytecode, i?)
+}
+
+/// Runs the constraints of `method`, if any, tracing where they failed
+fn trace_pact(method: &Method, args: &[PactType]) -> PactTrace {
+    let Some(pact) = method.get_pact() else {
+        return PactTrace::NotRun;
+    };
+    let data_table = pact.data_table.as_ref();
+    match interpret(args, data_table, &pact.bytecode) {
+        Ok(true) => PactTrace::Passed,
+        Ok(false) => PactTrace::Failed(match Constraint::decompile(&pact) {
+            Ok(constraint) => {
+                locate_deciding_comparison(&constraint, args, data_table, &pact.bytecode)
+            }
+            Err(_) => locate_comparison(args, data_table, &pact.bytecode, |outcome| {
+                *outcome == Ok(false)
+            }),
+        }),
+        Err(_) => PactTrace::Errored(locate_comparison(
+            args,
+            data_table,
+            &pact.bytecode,
+            Result::is_err,
+        )),
+    }
+}
+
+/// Validates as `validate_modules` does, recording how the result was reached
+pub(crate) fn explain_modules(
+    modules: &[Module],
+    module_name: &str,
+    method_name: &str,
+    args: &[PactType],
+) -> ValidationTrace {
+    let mut trace = ValidationTrace {
+        module_name: module_name.to_string(),
+        method_name: method_name.to_string(),
+        module: None,
+        method: None,
+        pact: PactTrace::NotRun,
+        result: Err(ValidationErr::NoPermission(RuntimeDomain::Module)),
+    };
+
+    if let Some(denied) = find_deny(modules, module_name) {
+        trace.module = Some(MatchedEntry::new(&denied.name, module_name, true));
+        return trace;
+    }
+    let Some(module) = find_module(modules, module_name) else {
+        return trace;
+    };
+    trace.module = Some(MatchedEntry::new(&module.name, module_name, false));
+
+    trace.result = Err(ValidationErr::NoPermission(RuntimeDomain::Method));
+    if let Some(denied) = module.find_deny(method_name) {
+        trace.method = Some(MatchedEntry::new(&denied.name, method_name, true));
+        return trace;
+    }
+    let Some(method) = module.get_method(method_name) else {
+        return trace;
+    };
+    trace.method = Some(MatchedEntry::new(&method.name, method_name, false));
+
+    trace.pact = trace_pact(method, args);
+    trace.result = match trace.pact {
+        PactTrace::NotRun | PactTrace::Passed => Ok(()),
+        PactTrace::Failed(_) => Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments)),
+        PactTrace::Errored(_) => Err(ValidationErr::ConstraintsInterpretation),
+    };
+    trace
+}
@@ -0,0 +1,245 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Index
+//!
+//! Indexed lookups of TRNNut for use in TRN
+//!
+//! `TRNNutIndex` is built once from a `TRNNutV0` and resolves module and method
+//! names the way `TRNNutV0::get_module` and `Module::get_method` do. Exact
+//! names are found in O(log n), then patterns are tried most specific first,
+//! so only names no exact entry matches visit the, usually few, patterns.
+//!
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::cmp::Reverse;
+use pact::types::PactType;
+
+use crate::{
+    method::Method,
+    module::Module,
+    pattern::{specificity, split_pattern},
+    trnnut::validate_constraints,
+    RuntimeDomain, TRNNutV0, ValidationErr,
+};
+
+/// Positions of granting and deny entries by name, resolved as `most_specific` resolves them
+struct NameIndex<'a> {
+    /// The first granting entry of each name, patterns included by their literal name
+    exact: BTreeMap<&'a str, usize>,
+    /// Granting patterns, most specific first and later first when equally specific
+    patterns: Vec<(&'a str, usize)>,
+    /// The names of deny entries, patterns included by their literal name
+    denied: BTreeSet<&'a str>,
+    deny_patterns: Vec<&'a str>,
+}
+
+impl<'a> NameIndex<'a> {
+    /// Indexes entries given by their name and whether they are deny entries
+    fn new(entries: impl Iterator<Item = (&'a str, bool)>) -> Self {
+        let mut index = Self {
+            exact: BTreeMap::new(),
+            patterns: Vec::new(),
+            denied: BTreeSet::new(),
+            deny_patterns: Vec::new(),
+        };
+        for (position, (name, deny)) in entries.enumerate() {
+            let is_pattern = split_pattern(name).is_some();
+            if deny {
+                index.denied.insert(name);
+                if is_pattern {
+                    index.deny_patterns.push(name);
+                }
+            } else {
+                index.exact.entry(name).or_insert(position);
+                if is_pattern {
+                    index.patterns.push((name, position));
+                }
+            }
+        }
+        index.patterns.sort_by_key(|(name, position)| {
+            Reverse((
+                split_pattern(name).map(|(prefix, suffix)| (prefix.len(), suffix.len())),
+                *position,
+            ))
+        });
+        index
+    }
+
+    /// Returns the position of the granting entry most specifically matching `name`
+    fn get(&self, name: &str) -> Option<usize> {
+        self.exact.get(name).copied().or_else(|| {
+            self.patterns
+                .iter()
+                .find(|(pattern, _)| specificity(pattern, name).is_some())
+                .map(|(_, position)| *position)
+        })
+    }
+
+    /// Returns whether any deny entry matches `name`
+    fn denies(&self, name: &str) -> bool {
+        self.denied.contains(name)
+            || self
+                .deny_patterns
+                .iter()
+                .any(|pattern| specificity(pattern, name).is_some())
+    }
+}
+
+/// An index of the module and method names of a `TRNNutV0`, offering its lookups and validation
+pub struct TRNNutIndex<'a> {
+    modules: &'a [Module],
+    module_index: NameIndex<'a>,
+    /// The method index of each module, by position
+    method_indexes: Vec<NameIndex<'a>>,
+}
+
+impl<'a> TRNNutIndex<'a> {
+    pub fn new(trnnut: &'a TRNNutV0) -> Self {
+        let modules = trnnut.modules.as_slice();
+        Self {
+            modules,
+            module_index: NameIndex::new(modules.iter().map(|m| (m.name.as_str(), m.deny))),
+            method_indexes: modules
+                .iter()
+                .map(|module| {
+                    NameIndex::new(module.methods.iter().map(|m| (m.name.as_str(), m.deny)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the position of the module granting `module`, unless it is denied
+    fn granted_module(&self, module: &str) -> Option<usize> {
+        if self.module_index.denies(module) {
+            None
+        } else {
+            self.module_index.get(module)
+        }
+    }
+
+    /// Returns the module, as `TRNNutV0::get_module` does
+    pub fn get_module(&self, module: &str) -> Option<&'a Module> {
+        self.module_index.get(module).map(|i| &self.modules[i])
+    }
+
+    /// Returns the method of the module, as `get_module` then `Module::get_method` do
+    pub fn get_method(&self, module: &str, method: &str) -> Option<&'a Method> {
+        let i = self.module_index.get(module)?;
+        let j = self.method_indexes[i].get(method)?;
+        Some(&self.modules[i].methods[j])
+    }
+
+    /// Validates a runtime module as `TRNNutV0::validate_module` does
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails with the type of error embedded in `RuntimeDomain`
+    pub fn validate_module(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        let i = self
+            .granted_module(module_name)
+            .ok_or(ValidationErr::NoPermission(RuntimeDomain::Module))?;
+        let methods = &self.method_indexes[i];
+        let j = if methods.denies(method_name) {
+            None
+        } else {
+            methods.get(method_name)
+        }
+        .ok_or(ValidationErr::NoPermission(RuntimeDomain::Method))?;
+        validate_constraints(&self.modules[i].methods[j], args)
+    }
+}
+
+impl<'a> From<&'a TRNNutV0> for TRNNutIndex<'a> {
+    fn from(trnnut: &'a TRNNutV0) -> Self {
+        Self::new(trnnut)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TRNNutIndex;
+    use crate::{constraint::arg, method::Method, module::Module, tests::make_trnnut};
+    use pact::types::{Numeric, PactType};
+
+    #[test]
+    fn it_looks_up_as_the_trnnut() {
+        let trnnut = make_trnnut(vec![
+            Module::new("Balances").methods(vec![
+                Method::new("transfer").constrain(arg(0).le(1000)).unwrap(),
+                Method::new("transfer_*").block_cooldown(5),
+                Method::new("transfer_*").block_cooldown(6),
+                Method::new("transfer_keep_*").block_cooldown(7),
+                Method::new("*_all").block_cooldown(8),
+                Method::new("transfer_all").deny(),
+                Method::new("*"),
+            ]),
+            Module::new("nft::*").methods(vec![Method::new("mint")]),
+            Module::new("nft::Marketplace").methods(vec![Method::new("list")]),
+            Module::new("Sudo").deny(),
+            Module::new("S*").methods(vec![Method::new("*")]),
+            Module::new("*").methods(vec![Method::new("remark")]),
+        ]);
+        let index = TRNNutIndex::new(&trnnut);
+        let names = [
+            ("Balances", "transfer"),
+            ("Balances", "transfer_keep_alive"),
+            ("Balances", "transfer_all"),
+            ("Balances", "transfer_*"),
+            ("Balances", "burn_all"),
+            ("Balances", "burn"),
+            ("nft::Marketplace", "list"),
+            ("nft::Marketplace", "mint"),
+            ("nft::Collection", "mint"),
+            ("Sudo", "sudo"),
+            ("Staking", "bond"),
+            ("System", "remark"),
+        ];
+
+        for (module, method) in names {
+            assert_eq!(
+                index.get_module(module).map(|m| &m.name),
+                trnnut.get_module(module).map(|m| &m.name),
+                "{module}"
+            );
+            assert_eq!(
+                index.get_method(module, method),
+                trnnut.get_module(module).and_then(|m| m.get_method(method)),
+                "{module}::{method}"
+            );
+            assert_eq!(
+                index.validate_module(module, method, &[PactType::Numeric(Numeric(10))]),
+                trnnut.validate_module(module, method, &[PactType::Numeric(Numeric(10))]),
+                "{module}::{method}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_prefers_later_patterns_when_equally_specific() {
+        let trnnut = make_trnnut(vec![Module::new("Balances").methods(vec![
+            Method::new("transfer").constrain(arg(0).le(1000)).unwrap(),
+            Method::new("transfer_*").block_cooldown(5),
+            Method::new("transfer_*").block_cooldown(6),
+        ])]);
+        let index = TRNNutIndex::from(&trnnut);
+
+        assert_eq!(
+            index
+                .get_method("Balances", "transfer_all")
+                .and_then(|m| m.block_cooldown),
+            Some(6)
+        );
+        assert_eq!(
+            index.validate_module("Balances", "transfer", &[PactType::Numeric(Numeric(1001))]),
+            trnnut.validate_module("Balances", "transfer", &[PactType::Numeric(Numeric(1001))])
+        );
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - JSON
+//!
+//! Canonical JSON representation of TRNNut
+//!
+//! Unlike the serde derives of `TRNNutV0`, which write constraints as raw byte
+//! arrays, the canonical form writes constraints as the Pact data table and hex
+//! bytecode, and records the JSON format and TRNNut versions:
+//!
+//! ```json
+//! {
+//!   "format": 1,
+//!   "version": 0,
+//!   "modules": [{
+//!     "name": "Balances",
+//!     "blockCooldown": 10,
+//!     "methods": [{
+//!       "name": "transfer",
+//!       "constraints": {
+//!         "dataTable": [{ "numeric": 1000 }, { "string": "AUD" }],
+//!         "bytecode": "0x02000010"
+//!       }
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Constraints which are not a canonically encoded Pact contract are written
+//! as `{ "raw": "0x…" }`. The JSON Schema, generated with the `json-schema`
+//! feature, is published at `schema/trnnut.schema.json`.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use pact::types::{Contract as PactContract, DataTable, Numeric, PactType, StringLike};
+
+use crate::{
+    method::Method,
+    module::Module,
+    name::{MethodName, ModuleName, NameError},
+    TRNNut, TRNNutV0, TRNNutV1,
+};
+
+/// Version of the canonical JSON format written by this crate
+pub const JSON_FORMAT: u16 = 1;
+
+/// Error which may occur while converting canonical JSON into a TRNNut
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JsonError {
+    UnsupportedFormat(u16),
+    UnsupportedVersion(u16),
+    InvalidHex,
+    /// A version 0 module or method name which would not encode intact
+    InvalidName {
+        name: String,
+        error: NameError,
+    },
+    /// Raw constraints which are not a Pact contract
+    InvalidConstraints {
+        module: String,
+        method: String,
+    },
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedFormat(format) => write!(f, "unsupported JSON format {format}"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported TRNNut version {version}"),
+            Self::InvalidHex => write!(f, "expected 0x prefixed hex"),
+            Self::InvalidName { name, error } => write!(f, "invalid name {name:?}: {error}"),
+            Self::InvalidConstraints { module, method } => {
+                write!(f, "method {module}::{method} has invalid constraints")
+            }
+        }
+    }
+}
+
+/// A TRNNut in canonical JSON
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "json-schema", schemars(title = "TRNNut"))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TRNNutJson {
+    /// Version of the JSON format, see `JSON_FORMAT`
+    pub format: u16,
+    /// Encoding version of the TRNNut
+    pub version: u16,
+    pub modules: Vec<ModuleJson>,
+}
+
+/// A module in canonical JSON
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ModuleJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_cooldown: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deny: bool,
+    pub methods: Vec<MethodJson>,
+}
+
+/// A method in canonical JSON
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MethodJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_cooldown: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deny: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<ConstraintsJson>,
+}
+
+/// Method constraints in canonical JSON
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(untagged, deny_unknown_fields)]
+pub enum ConstraintsJson {
+    /// A Pact contract
+    Pact {
+        #[serde(rename = "dataTable")]
+        data_table: Vec<PactValueJson>,
+        #[cfg_attr(
+            feature = "json-schema",
+            schemars(regex(pattern = r"^0x([0-9a-fA-F]{2})*$"))
+        )]
+        bytecode: String,
+    },
+    /// Bytes which are not a canonically encoded Pact contract
+    Raw {
+        #[cfg_attr(
+            feature = "json-schema",
+            schemars(regex(pattern = r"^0x([0-9a-fA-F]{2})*$"))
+        )]
+        raw: String,
+    },
+}
+
+/// A Pact data table item in canonical JSON
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub enum PactValueJson {
+    Numeric(u64),
+    /// A string-like item which is valid UTF-8
+    String(String),
+    /// Any other string-like item, as 0x prefixed hex
+    Bytes(
+        #[cfg_attr(
+            feature = "json-schema",
+            schemars(regex(pattern = r"^0x([0-9a-fA-F]{2})*$"))
+        )]
+        String,
+    ),
+    List(Vec<PactValueJson>),
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(2 + bytes.len() * 2);
+    hex.push_str("0x");
+    for byte in bytes {
+        hex.push(char::from(DIGITS[usize::from(byte >> 4)]));
+        hex.push(char::from(DIGITS[usize::from(byte & 0x0f)]));
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, JsonError> {
+    let digits = hex.strip_prefix("0x").ok_or(JsonError::InvalidHex)?;
+    if digits.len() % 2 != 0 {
+        return Err(JsonError::InvalidHex);
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or(JsonError::InvalidHex)
+        })
+        .collect()
+}
+
+impl From<&PactType> for PactValueJson {
+    fn from(value: &PactType) -> Self {
+        match value {
+            PactType::Numeric(Numeric(number)) => Self::Numeric(*number),
+            PactType::StringLike(StringLike(bytes)) => match core::str::from_utf8(bytes) {
+                Ok(string) => Self::String(string.to_string()),
+                Err(_) => Self::Bytes(to_hex(bytes)),
+            },
+            PactType::List(items) => Self::List(items.iter().map(Self::from).collect()),
+        }
+    }
+}
+
+impl TryFrom<&PactValueJson> for PactType {
+    type Error = JsonError;
+
+    fn try_from(value: &PactValueJson) -> Result<Self, Self::Error> {
+        Ok(match value {
+            PactValueJson::Numeric(number) => Self::Numeric(Numeric(*number)),
+            PactValueJson::String(string) => {
+                Self::StringLike(StringLike(string.as_bytes().to_vec()))
+            }
+            PactValueJson::Bytes(hex) => Self::StringLike(StringLike(from_hex(hex)?)),
+            PactValueJson::List(items) => {
+                Self::List(items.iter().map(Self::try_from).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+}
+
+impl From<&[u8]> for ConstraintsJson {
+    fn from(constraints: &[u8]) -> Self {
+        if let Ok(pact) = PactContract::decode(constraints) {
+            let mut encoded = Vec::<u8>::default();
+            pact.encode(&mut encoded);
+            if encoded == constraints {
+                return Self::Pact {
+                    data_table: pact
+                        .data_table
+                        .as_ref()
+                        .iter()
+                        .map(PactValueJson::from)
+                        .collect(),
+                    bytecode: to_hex(&pact.bytecode),
+                };
+            }
+        }
+        Self::Raw {
+            raw: to_hex(constraints),
+        }
+    }
+}
+
+impl ConstraintsJson {
+    /// Returns the encoded constraints
+    ///
+    /// # Errors
+    ///
+    /// Returns `JsonError::InvalidHex` if the bytecode or any bytes are not hex
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsonError> {
+        match self {
+            Self::Pact {
+                data_table,
+                bytecode,
+            } => {
+                let pact = PactContract {
+                    data_table: DataTable::new(
+                        data_table
+                            .iter()
+                            .map(PactType::try_from)
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    bytecode: from_hex(bytecode)?,
+                };
+                let mut encoded = Vec::<u8>::default();
+                pact.encode(&mut encoded);
+                Ok(encoded)
+            }
+            Self::Raw { raw } => from_hex(raw),
+        }
+    }
+}
+
+impl From<&Method> for MethodJson {
+    fn from(method: &Method) -> Self {
+        Self {
+            name: method.name.clone(),
+            block_cooldown: method.block_cooldown,
+            deny: method.deny,
+            // Empty constraints are not encoded, so are left out here too
+            constraints: method
+                .constraints
+                .as_deref()
+                .filter(|c| !c.is_empty())
+                .map(ConstraintsJson::from),
+        }
+    }
+}
+
+impl From<&Module> for ModuleJson {
+    fn from(module: &Module) -> Self {
+        Self {
+            name: module.name.clone(),
+            block_cooldown: module.block_cooldown,
+            deny: module.deny,
+            methods: module.methods.iter().map(MethodJson::from).collect(),
+        }
+    }
+}
+
+impl From<&TRNNut> for TRNNutJson {
+    fn from(trnnut: &TRNNut) -> Self {
+        Self {
+            format: JSON_FORMAT,
+            version: trnnut.version(),
+            modules: trnnut.modules().iter().map(ModuleJson::from).collect(),
+        }
+    }
+}
+
+/// Validates a module or method name as `N`
+fn validate_name<N: for<'a> TryFrom<&'a str, Error = NameError>>(
+    name: &str,
+) -> Result<(), JsonError> {
+    N::try_from(name)
+        .map(|_| ())
+        .map_err(|error| JsonError::InvalidName {
+            name: name.to_string(),
+            error,
+        })
+}
+
+impl ModuleJson {
+    /// Converts to a `Module`, names must fit version 0 when `version` is 0
+    fn to_module(&self, version: u16) -> Result<Module, JsonError> {
+        if version == 0 {
+            validate_name::<ModuleName>(&self.name)?;
+        }
+        let mut methods = Vec::<Method>::default();
+        for method in &self.methods {
+            if version == 0 {
+                validate_name::<MethodName>(&method.name)?;
+            }
+            let constraints = method
+                .constraints
+                .as_ref()
+                .map(ConstraintsJson::to_bytes)
+                .transpose()?;
+            if constraints
+                .as_ref()
+                .is_some_and(|c| PactContract::decode(c).is_err())
+            {
+                return Err(JsonError::InvalidConstraints {
+                    module: self.name.clone(),
+                    method: method.name.clone(),
+                });
+            }
+            methods.push(Method {
+                name: method.name.clone(),
+                block_cooldown: method.block_cooldown,
+                constraints,
+                deny: method.deny,
+            });
+        }
+        Ok(Module {
+            name: self.name.clone(),
+            block_cooldown: self.block_cooldown,
+            methods,
+            deny: self.deny,
+        })
+    }
+}
+
+impl TryFrom<&TRNNutJson> for TRNNut {
+    type Error = JsonError;
+
+    fn try_from(json: &TRNNutJson) -> Result<Self, Self::Error> {
+        if json.format != JSON_FORMAT {
+            return Err(JsonError::UnsupportedFormat(json.format));
+        }
+        let modules = json
+            .modules
+            .iter()
+            .map(|module| module.to_module(json.version))
+            .collect::<Result<Vec<_>, _>>()?;
+        match json.version {
+            0 => Ok(Self::V0(TRNNutV0 { modules })),
+            1 => Ok(Self::V1(TRNNutV1 { modules })),
+            version => Err(JsonError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Returns the JSON Schema of the canonical JSON format
+#[cfg(feature = "json-schema")]
+pub fn json_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(TRNNutJson)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        from_hex, to_hex, ConstraintsJson, JsonError, ModuleJson, NameError, PactValueJson,
+        TRNNutJson, JSON_FORMAT,
+    };
+    use crate::{method::Method, module::Module, TRNNut, TRNNutV0, TRNNutV1, TryEncode};
+    use codec::Decode;
+    use pact::{
+        interpreter::{Comparator, OpCode, OpComp},
+        types::{Contract as PactContract, DataTable, Numeric, PactType, StringLike},
+    };
+    use std::convert::TryFrom;
+
+    fn make_pact() -> PactContract {
+        PactContract {
+            data_table: DataTable::new(vec![
+                PactType::Numeric(Numeric(1000)),
+                PactType::StringLike(StringLike(b"AUD".to_vec())),
+                PactType::StringLike(StringLike(vec![0xff, 0x00])),
+                PactType::List(vec![PactType::Numeric(Numeric(1))]),
+            ]),
+            bytecode: [
+                OpCode::COMP(Comparator::new(OpComp::GTE).invert()).into(),
+                0x00_u8,
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x11_u8,
+            ]
+            .to_vec(),
+        }
+    }
+
+    fn make_constraints() -> Vec<u8> {
+        let mut constraints = Vec::<u8>::default();
+        make_pact().encode(&mut constraints);
+        constraints
+    }
+
+    fn make_trnnut() -> TRNNut {
+        TRNNut::V1(TRNNutV1 {
+            modules: vec![
+                Module::new("Balances").block_cooldown(10).methods(vec![
+                    Method::new("transfer").constraints(make_constraints()),
+                    Method::new("burn").deny(),
+                ]),
+                Module::new("*").methods(vec![Method::new("*").block_cooldown(5)]),
+            ],
+        })
+    }
+
+    #[test]
+    fn it_converts_hex() {
+        assert_eq!(to_hex(&[0x00, 0xab, 0x10]), "0x00ab10");
+        assert_eq!(from_hex("0x00AB10"), Ok(vec![0x00, 0xab, 0x10]));
+        assert_eq!(from_hex("0x"), Ok(vec![]));
+        assert_eq!(from_hex("00ab"), Err(JsonError::InvalidHex));
+        assert_eq!(from_hex("0x0ab"), Err(JsonError::InvalidHex));
+        assert_eq!(from_hex("0x0g"), Err(JsonError::InvalidHex));
+        assert_eq!(from_hex("0xé0"), Err(JsonError::InvalidHex));
+    }
+
+    #[test]
+    fn it_writes_canonical_json() {
+        let json = serde_json::to_value(TRNNutJson::from(&make_trnnut())).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "format": 1,
+                "version": 1,
+                "modules": [
+                    {
+                        "name": "Balances",
+                        "blockCooldown": 10,
+                        "methods": [
+                            {
+                                "name": "transfer",
+                                "constraints": {
+                                    "dataTable": [
+                                        { "numeric": 1000 },
+                                        { "string": "AUD" },
+                                        { "bytes": "0xff00" },
+                                        { "list": [{ "numeric": 1 }] },
+                                    ],
+                                    "bytecode": to_hex(&make_pact().bytecode),
+                                },
+                            },
+                            { "name": "burn", "deny": true },
+                        ],
+                    },
+                    {
+                        "name": "*",
+                        "methods": [{ "name": "*", "blockCooldown": 5 }],
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn it_round_trips_json_and_binary() {
+        for trnnut in [
+            make_trnnut(),
+            TRNNut::V0(TRNNutV0 {
+                modules: vec![Module::new("Balances")
+                    .methods(vec![Method::new("transfer").constraints(make_constraints())])],
+            }),
+            TRNNut::V1(TRNNutV1 {
+                modules: vec![Module::new("Sudo").deny()],
+            }),
+        ] {
+            let encoded = trnnut.try_encode().unwrap();
+            let json = serde_json::to_string(&TRNNutJson::from(&trnnut)).unwrap();
+
+            let parsed: TRNNutJson = serde_json::from_str(&json).unwrap();
+            let decoded = TRNNut::try_from(&parsed).unwrap();
+            assert_eq!(decoded, trnnut);
+            assert_eq!(decoded.try_encode(), Ok(encoded.clone()));
+            assert_eq!(
+                TRNNutJson::from(&TRNNut::decode(&mut &encoded[..]).unwrap()),
+                parsed
+            );
+        }
+    }
+
+    #[test]
+    fn it_writes_non_canonical_constraints_raw() {
+        let mut constraints = make_constraints();
+        constraints.truncate(1);
+
+        assert_eq!(
+            ConstraintsJson::from(&constraints[..]),
+            ConstraintsJson::Raw {
+                raw: "0x00".to_string()
+            }
+        );
+        assert_eq!(
+            ConstraintsJson::from(&make_constraints()[..]).to_bytes(),
+            Ok(make_constraints())
+        );
+    }
+
+    #[test]
+    fn it_reads_raw_constraints() {
+        let json = serde_json::json!({
+            "format": 1,
+            "version": 0,
+            "modules": [{
+                "name": "Balances",
+                "methods": [{ "name": "transfer", "constraints": { "raw": to_hex(&make_constraints()) } }],
+            }],
+        });
+        let trnnut = TRNNut::try_from(&serde_json::from_value::<TRNNutJson>(json).unwrap());
+
+        assert_eq!(
+            trnnut.map(|t| t.modules()[0].methods[0].constraints.clone()),
+            Ok(Some(make_constraints()))
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_json() {
+        let mut json = TRNNutJson::from(&make_trnnut());
+        json.format = 2;
+        assert_eq!(
+            TRNNut::try_from(&json),
+            Err(JsonError::UnsupportedFormat(2))
+        );
+
+        json.format = JSON_FORMAT;
+        json.version = 2;
+        assert_eq!(
+            TRNNut::try_from(&json),
+            Err(JsonError::UnsupportedVersion(2))
+        );
+
+        json.version = 0;
+        json.modules[0].methods[0].constraints = Some(ConstraintsJson::Raw {
+            raw: "0xff".to_string(),
+        });
+        assert_eq!(
+            TRNNut::try_from(&json),
+            Err(JsonError::InvalidConstraints {
+                module: "Balances".to_string(),
+                method: "transfer".to_string()
+            })
+        );
+
+        json.modules[0].methods[0].constraints = Some(ConstraintsJson::Pact {
+            data_table: vec![PactValueJson::Bytes("ff".to_string())],
+            bytecode: "0x".to_string(),
+        });
+        assert_eq!(TRNNut::try_from(&json), Err(JsonError::InvalidHex));
+
+        json.modules[0].methods[0].constraints = None;
+        json.modules[0].methods[0].name = "a".repeat(33);
+        assert_eq!(
+            TRNNut::try_from(&json),
+            Err(JsonError::InvalidName {
+                name: "a".repeat(33),
+                error: NameError::TooLong(33),
+            })
+        );
+        // Version 1 names are unbounded
+        json.version = 1;
+        assert!(TRNNut::try_from(&json).is_ok());
+
+        assert!(serde_json::from_value::<ModuleJson>(serde_json::json!({
+            "name": "Balances",
+            "block_cooldown": 1,
+            "methods": [],
+        }))
+        .is_err());
+        assert!(
+            serde_json::from_value::<ConstraintsJson>(serde_json::json!({
+                "raw": "0x00",
+                "bytecode": "0x00",
+            }))
+            .is_err()
+        );
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn published_schema_is_up_to_date() {
+        let published: serde_json::Value =
+            serde_json::from_str(include_str!("../schema/trnnut.schema.json")).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(super::json_schema()).unwrap(),
+            published
+        );
+    }
+}
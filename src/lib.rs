@@ -11,16 +11,49 @@
 extern crate alloc;
 
 use alloc::fmt::{self, Display, Formatter};
-use codec::Input;
+use codec::{Decode, Encode, Input, Output};
 pub use core::convert::TryFrom;
+use pact::types::PactType;
 
+/// The Pact crate of constraints, for dependents to build the same `PactType`s as this crate
+pub use pact;
+
+pub mod attenuation;
+pub mod combine;
+pub mod constraint;
+pub mod cooldown;
+pub mod edit;
+pub mod encode;
+pub mod explain;
+pub mod index;
+pub mod json;
+pub mod limits;
+pub mod name;
+pub mod pattern;
+pub mod policy;
 pub mod trnnut;
+pub mod trnnut_ref;
+pub mod trnnut_v1;
 pub mod validation;
 pub mod method;
 pub mod module;
 
+use crate::module::Module;
+
 pub use crate::{
+    attenuation::AttenuationViolation,
+    combine::CombineError,
+    cooldown::{CooldownStore, InMemoryCooldownStore},
+    edit::EditError,
+    encode::{EncodeError, TryEncode},
+    explain::ValidationTrace,
+    index::TRNNutIndex,
+    json::TRNNutJson,
+    limits::Limits,
+    name::{MethodName, ModuleName, NameError},
     trnnut::TRNNutV0,
+    trnnut_ref::TRNNutRef,
+    trnnut_v1::TRNNutV1,
     validation::ValidationErr,
 };
 
@@ -55,3 +88,235 @@ pub trait PartialDecode: Sized {
     /// On failure, returns a `codec::Error`
     fn partial_decode<I: Input>(input: &mut I) -> Result<Self, codec::Error>;
 }
+
+pub trait LimitedDecode: Sized {
+    /// decode an input as `Decode` does, within `limits` instead of the default ones
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns a `codec::Error`
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error>;
+}
+
+pub trait StrictDecode: Sized {
+    /// decode an entire input, rejecting trailing bytes, undefined flag bits,
+    /// non-zero name padding and any input which does not re-encode identically
+    ///
+    /// # Errors
+    ///
+    /// On failure, returns a `codec::Error`
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error>;
+}
+
+/// Returns whether a fixed size name buffer is zero after its first NUL byte
+pub(crate) fn is_zero_padded(name_buf: &[u8]) -> bool {
+    name_buf
+        .iter()
+        .skip_while(|b| **b != 0)
+        .all(|b| *b == 0)
+}
+
+/// Checks that nothing `remaining` follows `decoded` and that it re-encodes as `input`
+pub(crate) fn ensure_canonical<T: TryEncode>(
+    decoded: T,
+    input: &[u8],
+    remaining: &[u8],
+) -> Result<T, codec::Error> {
+    if !remaining.is_empty() {
+        return Err(codec::Error::from("unexpected trailing bytes"));
+    }
+    match decoded.try_encode() {
+        Ok(encoded) if encoded == input => Ok(decoded),
+        _ => Err(codec::Error::from("non-canonical encoding")),
+    }
+}
+
+/// A versioned TRNNut
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TRNNut {
+    V0(TRNNutV0),
+    V1(TRNNutV1),
+}
+
+impl TRNNut {
+    /// Returns the encoding version of the TRNNut
+    pub fn version(&self) -> u16 {
+        match self {
+            Self::V0(_) => 0,
+            Self::V1(_) => 1,
+        }
+    }
+
+    /// Returns the modules of the TRNNut
+    pub fn modules(&self) -> &[Module] {
+        match self {
+            Self::V0(trnnut) => &trnnut.modules,
+            Self::V1(trnnut) => &trnnut.modules,
+        }
+    }
+
+    /// Returns the module, if it exists in the TRNNut
+    /// Exact names have priority over patterns, then longer pattern prefixes, then suffixes
+    pub fn get_module(&self, module: &str) -> Option<&Module> {
+        match self {
+            Self::V0(trnnut) => trnnut.get_module(module),
+            Self::V1(trnnut) => trnnut.get_module(module),
+        }
+    }
+
+    /// Validates a TRNNut runtime module, whatever its version
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails with the type of error embedded in `RuntimeDomain`
+    pub fn validate_module(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        match self {
+            Self::V0(trnnut) => trnnut.validate_module(module_name, method_name, args),
+            Self::V1(trnnut) => trnnut.validate_module(module_name, method_name, args),
+        }
+    }
+
+    /// Validates a TRNNut runtime module with a trace of how, whatever its version
+    pub fn validate_module_explain(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> ValidationTrace {
+        match self {
+            Self::V0(trnnut) => trnnut.validate_module_explain(module_name, method_name, args),
+            Self::V1(trnnut) => trnnut.validate_module_explain(module_name, method_name, args),
+        }
+    }
+
+    /// Validates a TRNNut runtime module and its block cooldowns, whatever its version
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails or a cooldown has not yet elapsed
+    pub fn validate_module_at<S: CooldownStore>(
+        &self,
+        block_number: u32,
+        store: &S,
+        holder: &[u8],
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        match self {
+            Self::V0(trnnut) => trnnut.validate_module_at(
+                block_number,
+                store,
+                holder,
+                module_name,
+                method_name,
+                args,
+            ),
+            Self::V1(trnnut) => trnnut.validate_module_at(
+                block_number,
+                store,
+                holder,
+                module_name,
+                method_name,
+                args,
+            ),
+        }
+    }
+}
+
+/// Renders the versioned TRNNut, see `Display for TRNNutV0`
+impl Display for TRNNut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V0(trnnut) => Display::fmt(trnnut, f),
+            Self::V1(trnnut) => Display::fmt(trnnut, f),
+        }
+    }
+}
+
+impl TryEncode for TRNNut {
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError> {
+        match self {
+            Self::V0(trnnut) => trnnut.try_encode_limited_to(buf, limits),
+            Self::V1(trnnut) => trnnut.try_encode_limited_to(buf, limits),
+        }
+    }
+}
+
+impl Encode for TRNNut {
+    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+        match self {
+            Self::V0(trnnut) => trnnut.encode_to(buf),
+            Self::V1(trnnut) => trnnut.encode_to(buf),
+        }
+    }
+}
+
+impl Decode for TRNNut {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::decode_limited(input, Limits::default())
+    }
+}
+
+impl LimitedDecode for TRNNut {
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error> {
+        let version = u16::from_le_bytes([input.read_byte()?, input.read_byte()?]);
+        match version {
+            0 => Ok(Self::V0(TRNNutV0::partial_decode_with(input, false, limits)?)),
+            1 => Ok(Self::V1(TRNNutV1::partial_decode_with(input, limits)?)),
+            _ => Err(codec::Error::from("unsupported trnnut version")),
+        }
+    }
+}
+
+impl StrictDecode for TRNNut {
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error> {
+        match input {
+            [0, 0, ..] => Ok(Self::V0(TRNNutV0::decode_strict(input)?)),
+            [1, 0, ..] => Ok(Self::V1(TRNNutV1::decode_strict(input)?)),
+            [_, _, ..] => Err(codec::Error::from("unsupported trnnut version")),
+            _ => Err(codec::Error::from("Not enough data to fill buffer")),
+        }
+    }
+}
+
+impl From<TRNNutV0> for TRNNut {
+    fn from(trnnut: TRNNutV0) -> Self {
+        Self::V0(trnnut)
+    }
+}
+
+impl From<TRNNutV1> for TRNNut {
+    fn from(trnnut: TRNNutV1) -> Self {
+        Self::V1(trnnut)
+    }
+}
+
+impl TryFrom<TRNNut> for TRNNutV0 {
+    type Error = codec::Error;
+    fn try_from(trnnut: TRNNut) -> Result<Self, Self::Error> {
+        match trnnut {
+            TRNNut::V0(trnnut) => Ok(trnnut),
+            TRNNut::V1(_) => Err(codec::Error::from("expected version : 0")),
+        }
+    }
+}
+
+impl TryFrom<TRNNut> for TRNNutV1 {
+    type Error = codec::Error;
+    fn try_from(trnnut: TRNNut) -> Result<Self, Self::Error> {
+        match trnnut {
+            TRNNut::V1(trnnut) => Ok(trnnut),
+            TRNNut::V0(_) => Err(codec::Error::from("expected version : 1")),
+        }
+    }
+}
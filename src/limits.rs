@@ -0,0 +1,63 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Limits
+//!
+//! Configurable limits applied while encoding and decoding TRNNuts
+//!
+
+use crate::method::MAX_CONSTRAINTS;
+
+/// Limits applied by `TryEncode::try_encode_limited` and `LimitedDecode`
+/// The default limits are those of `TryEncode::try_encode` and `Decode`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    pub(crate) max_constraints: usize,
+}
+
+impl Limits {
+    /// Set the maximum length of a method's constraints in bytes, up to `u16::MAX`
+    /// Version 0 encodes the length of longer constraints in two bytes
+    #[must_use]
+    pub fn max_constraints(mut self, max_constraints: usize) -> Self {
+        self.max_constraints = max_constraints.min(usize::from(u16::MAX));
+        self
+    }
+
+    /// Returns the maximum length of a method's constraints in bytes
+    pub fn get_max_constraints(&self) -> usize {
+        self.max_constraints
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_constraints: MAX_CONSTRAINTS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Limits;
+    use crate::method::MAX_CONSTRAINTS;
+
+    #[test]
+    fn it_defaults_to_max_constraints() {
+        assert_eq!(Limits::default().get_max_constraints(), MAX_CONSTRAINTS);
+        assert_eq!(
+            Limits::default().max_constraints(512).get_max_constraints(),
+            512
+        );
+    }
+
+    #[test]
+    fn it_caps_max_constraints_at_a_u16_length() {
+        assert_eq!(
+            Limits::default()
+                .max_constraints(usize::MAX)
+                .get_max_constraints(),
+            usize::from(u16::MAX)
+        );
+    }
+}
@@ -6,6 +6,7 @@
 //!
 
 use alloc::{
+    fmt::{self, Display, Formatter, Write},
     string::{String, ToString},
     vec::Vec,
 };
@@ -13,27 +14,78 @@ use codec::{Decode, Encode, Input, Output};
 use core::convert::TryFrom;
 use pact::types::Contract as PactContract;
 
-const BLOCK_COOLDOWN_MASK: u8 = 0x01;
-const CONSTRAINTS_MASK: u8 = 0x02;
-const MAX_CONSTRAINTS: usize = 256;
+use crate::constraint::{disassemble, Constraint, ConstraintError};
+use crate::encode::{EncodeError, TryEncode};
+use crate::name::{deserialize_method_name, MethodName, NameError};
+use crate::pattern::pattern_marker;
+use crate::policy::{write_constraints, write_name};
+use crate::{ensure_canonical, is_zero_padded, limits::Limits, LimitedDecode, StrictDecode};
+
+pub(crate) const BLOCK_COOLDOWN_MASK: u8 = 0x01;
+pub(crate) const CONSTRAINTS_MASK: u8 = 0x02;
+/// Reserved for deny entries, which only version 1 encodes. Decoders which ignore undefined
+/// flags would read a denied method as granted, so version 0 never encodes nor decodes it
+pub(crate) const DENY_MASK: u8 = 0x04;
+/// Constraints are preceded by a u16 length instead of a single byte
+pub(crate) const LONG_CONSTRAINTS_MASK: u8 = 0x08;
+/// The maximum length of constraints preceded by a single byte
+const MAX_SHORT_CONSTRAINTS: usize = 256;
+/// The default maximum length of encoded constraints, see `Limits`
+/// Longer constraints are rejected when encoding and decoding
+pub const MAX_CONSTRAINTS: usize = 4096;
 
 /// A TRN permission domain module method
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Method {
+    #[serde(deserialize_with = "deserialize_method_name")]
     pub name: String,
     pub block_cooldown: Option<u32>,
     pub constraints: Option<Vec<u8>>,
+    /// Denies the method instead of granting it, see `Module::denies`
+    #[serde(default)]
+    pub deny: bool,
 }
 
 impl Method {
+    /// Creates a Method named `name`, see `try_new` for names which may be invalid
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid `MethodName`, see `unbounded` for `TRNNutV1` names
     pub fn new(name: &str) -> Self {
+        Self::try_new(name).unwrap_or_else(|err| panic!("invalid method name {name:?}: {err}"))
+    }
+
+    /// Creates a Method without validating `name`, for `TRNNutV1`, which encodes names
+    /// of any length. Version 0 rejects names which are not a valid `MethodName` when encoding
+    pub fn unbounded(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            block_cooldown: None,
+            constraints: None,
+            deny: false,
+        }
+    }
+
+    /// Creates a Method with a validated name
+    pub fn named(name: MethodName) -> Self {
         Self {
             name: name.into(),
             block_cooldown: None,
             constraints: None,
+            deny: false,
         }
     }
 
+    /// Creates a Method, if `name` is a valid `MethodName`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NameError` if `name` would not encode intact
+    pub fn try_new(name: &str) -> Result<Self, NameError> {
+        Ok(Self::named(MethodName::try_from(name)?))
+    }
+
     pub fn block_cooldown(mut self, block_cooldown: u32) -> Self {
         self.block_cooldown = Some(block_cooldown);
         self
@@ -44,6 +96,23 @@ impl Method {
         self
     }
 
+    /// Sets the constraints to the Pact contract evaluating `constraint`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConstraintError` if `constraint` can't be compiled to Pact,
+    /// or its contract is longer than `MAX_CONSTRAINTS`
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn constrain(mut self, constraint: Constraint) -> Result<Self, ConstraintError> {
+        self.constraints = Some(constraint.to_bytes()?);
+        Ok(self)
+    }
+
+    pub fn deny(mut self) -> Self {
+        self.deny = true;
+        self
+    }
+
     /// Returns the Pact contract, if it exists in the Method
     pub fn get_pact(&self) -> Option<PactContract> {
         match &self.constraints {
@@ -56,18 +125,71 @@ impl Method {
             None => None,
         }
     }
+
+    /// Returns the constraints as a readable expression, e.g. `arg0 == 123 && arg1 == "test"`,
+    /// if the Method has any
+    ///
+    /// Constraints with no expression equivalent are listed as with `constraint::disassemble`.
+    pub fn describe_constraints(&self) -> Option<String> {
+        let constraints = self.constraints.as_ref().filter(|c| !c.is_empty())?;
+        let Some(pact) = self.get_pact() else {
+            let mut description = String::from("invalid constraints 0x");
+            for byte in constraints {
+                let _ = write!(description, "{byte:02x}");
+            }
+            return Some(description);
+        };
+        match Constraint::decompile(&pact) {
+            Ok(constraint) => Some(constraint.to_string()),
+            Err(_) => Some(disassemble(&pact)),
+        }
+    }
 }
 
-impl Encode for Method {
-    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+/// Renders as in the policy language, e.g. `method transfer cooldown 10 where arg0 <= 1000`
+/// The alternate form, `{:#}`, marks pattern names, e.g. `method transfer_* (pattern)`
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.deny {
+            write!(f, "deny ")?;
+        }
+        write!(f, "method ")?;
+        write_name(f, &self.name)?;
+        if let Some(marker) = pattern_marker(&self.name).filter(|_| f.alternate()) {
+            write!(f, " ({marker})")?;
+        }
+        if let Some(cooldown) = self.block_cooldown {
+            write!(f, " cooldown {cooldown}")?;
+        }
+        write_constraints(f, self)
+    }
+}
+
+impl TryEncode for Method {
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError> {
+        MethodName::try_from(self.name.as_str())?;
+        if self.deny {
+            return Err(EncodeError::MethodDenyUnsupported {
+                method: self.name.clone(),
+            });
+        }
         let has_cooldown_byte: u8 = if self.block_cooldown.is_some() {
             BLOCK_COOLDOWN_MASK
         } else {
             0
         };
         let has_constraints_byte: u8 = if let Some(constraints) = &self.constraints {
+            if constraints.len() > limits.max_constraints {
+                return Err(EncodeError::ConstraintsTooLong);
+            }
             if constraints.is_empty() {
                 0
+            } else if constraints.len() > MAX_SHORT_CONSTRAINTS {
+                CONSTRAINTS_MASK | LONG_CONSTRAINTS_MASK
             } else {
                 CONSTRAINTS_MASK
             }
@@ -77,9 +199,7 @@ impl Encode for Method {
         buf.push_byte(has_cooldown_byte | has_constraints_byte);
 
         let mut name = [0_u8; 32];
-        let length = 32.min(self.name.len());
-
-        name[0..length].clone_from_slice(&self.name.as_bytes()[0..length]);
+        name[0..self.name.len()].clone_from_slice(self.name.as_bytes());
 
         buf.write(&name);
 
@@ -89,26 +209,59 @@ impl Encode for Method {
             }
         }
 
-        if let Some(constraints) = &self.constraints {
-            let constraints_count =
-                u8::try_from(MAX_CONSTRAINTS.min(constraints.len()).wrapping_sub(1));
-            if let Ok(len_byte) = constraints_count {
-                let len: usize = len_byte.into();
+        if let Some(constraints) = self.constraints.as_ref().filter(|c| !c.is_empty()) {
+            if let Ok(len_byte) = u8::try_from(constraints.len().wrapping_sub(1)) {
                 buf.push_byte(len_byte);
-                buf.write(&constraints[0..=len]);
+                buf.write(constraints);
+            } else if let Ok(len) = u16::try_from(constraints.len()) {
+                buf.write(&len.to_le_bytes());
+                buf.write(constraints);
             }
         }
+
+        Ok(())
     }
 }
 
-impl Decode for Method {
-    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+impl Encode for Method {
+    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+        // Failures are reported by `TryEncode`, nothing is written here
+        let _ = self.try_encode_to(buf);
+    }
+}
+
+impl Method {
+    /// Decode a method within `limits`, when `strict` undefined flag bits and
+    /// non-zero name padding are rejected
+    pub(crate) fn decode_with<I: Input>(
+        input: &mut I,
+        strict: bool,
+        limits: Limits,
+    ) -> Result<Self, codec::Error> {
         let block_cooldown_and_constraints = input.read_byte()?;
+        // Deny entries are rejected once the name is read, strict or not
+        let mut undefined_flags =
+            block_cooldown_and_constraints & !(BLOCK_COOLDOWN_MASK | CONSTRAINTS_MASK | DENY_MASK);
+        // Long constraints are only defined for methods with constraints
+        if block_cooldown_and_constraints & CONSTRAINTS_MASK == CONSTRAINTS_MASK {
+            undefined_flags &= !LONG_CONSTRAINTS_MASK;
+        }
+        if strict && undefined_flags != 0 {
+            return Err(codec::Error::from("undefined method flags"));
+        }
 
         let mut name_buf: [u8; 32] = Default::default();
         input
             .read(&mut name_buf)
             .map_err(|_| "expected 32 byte method name")?;
+        if block_cooldown_and_constraints & DENY_MASK == DENY_MASK {
+            return Err(codec::Error::from(
+                "deny entries are only defined for version 1",
+            ));
+        }
+        if strict && !is_zero_padded(&name_buf) {
+            return Err(codec::Error::from("method names should be zero padded"));
+        }
         let name = core::str::from_utf8(&name_buf)
             .map_err(|_| codec::Error::from("method names should be utf8 encoded"))?
             .trim_matches(char::from(0))
@@ -128,7 +281,18 @@ impl Decode for Method {
 
         let constraints: Option<Vec<u8>> =
             if (block_cooldown_and_constraints & CONSTRAINTS_MASK) == CONSTRAINTS_MASK {
-                let constraints_length = (input.read_byte()?).saturating_add(1);
+                let constraints_length = if block_cooldown_and_constraints & LONG_CONSTRAINTS_MASK
+                    == LONG_CONSTRAINTS_MASK
+                {
+                    let length =
+                        usize::from(u16::from_le_bytes([input.read_byte()?, input.read_byte()?]));
+                    if length > limits.max_constraints {
+                        return Err(codec::Error::from("constraints are too long"));
+                    }
+                    length
+                } else {
+                    usize::from(input.read_byte()?) + 1
+                };
                 let mut constraints_buf = Vec::<u8>::default();
                 for _ in 0..constraints_length {
                     constraints_buf.push(input.read_byte()?);
@@ -145,13 +309,42 @@ impl Decode for Method {
             name,
             block_cooldown,
             constraints,
+            deny: false,
         })
     }
 }
 
+impl Decode for Method {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::decode_with(input, false, Limits::default())
+    }
+}
+
+impl LimitedDecode for Method {
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error> {
+        Self::decode_with(input, false, limits)
+    }
+}
+
+impl StrictDecode for Method {
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error> {
+        let mut remaining = input;
+        let method = Self::decode_with(&mut remaining, true, Limits::default())?;
+        ensure_canonical(method, input, remaining)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Method, BLOCK_COOLDOWN_MASK, CONSTRAINTS_MASK};
+    use super::{
+        Method, BLOCK_COOLDOWN_MASK, CONSTRAINTS_MASK, DENY_MASK, LONG_CONSTRAINTS_MASK,
+        MAX_CONSTRAINTS,
+    };
+    use crate::constraint::{arg, Constraint, ConstraintError};
+    use crate::encode::{EncodeError, TryEncode};
+    use crate::limits::Limits;
+    use crate::name::NameError;
+    use crate::{LimitedDecode, StrictDecode};
     use codec::{Decode, Encode};
     use std::assert_eq;
 
@@ -163,6 +356,22 @@ mod test {
         assert_eq!(method.name, "TestMethod");
         assert_eq!(method.block_cooldown, None);
         assert_eq!(method.constraints, None);
+        assert!(!method.deny);
+    }
+
+    #[test]
+    fn it_validates_names() {
+        assert_eq!(Method::try_new("TestMethod"), Ok(Method::new("TestMethod")));
+        assert_eq!(
+            Method::try_new(&format!("{}é", "a".repeat(31))),
+            Err(NameError::TooLong(33))
+        );
+        assert_eq!(Method::try_new("Test\0Method"), Err(NameError::NulByte));
+        let name = "a".repeat(33);
+        assert!(serde_json::from_str::<Method>(&format!(
+            r#"{{"name":"{name}","block_cooldown":null,"constraints":null}}"#
+        ))
+        .is_err());
     }
 
     // Encoding Tests
@@ -178,11 +387,27 @@ mod test {
     }
 
     #[test]
-    fn it_encodes_only_32_characters_for_name() {
-        let method = Method::new("I am Sam, I am Sam, Sam I am; That Sam I am, That Sam I am, I do not like that Sam I am");
-        let expected_length = 33;
+    fn it_does_not_encode_names_longer_than_32_bytes() {
+        let method = Method::unbounded("I am Sam, I am Sam, Sam I am; That Sam I am, That Sam I am, I do not like that Sam I am");
+
+        assert_eq!(method.try_encode(), Err(EncodeError::NameTooLong));
+        assert_eq!(method.encode(), Vec::<u8>::default());
+    }
+
+    #[test]
+    fn it_does_not_encode_names_containing_nul() {
+        let method = Method::unbounded("Test\0Method");
 
-        assert_eq!(method.encode().len(), expected_length);
+        assert_eq!(method.try_encode(), Err(EncodeError::NameContainsNul));
+        assert_eq!(method.encode(), Vec::<u8>::default());
+    }
+
+    #[test]
+    fn it_encodes_a_32_byte_name() {
+        let method = Method::new(&"a".repeat(32));
+        let expected: Vec<u8> = [vec![0_u8], vec![b'a'; 32]].concat();
+
+        assert_eq!(method.try_encode(), Ok(expected));
     }
 
     #[test]
@@ -220,6 +445,76 @@ mod test {
         assert_eq!(method.encode(), expected);
     }
 
+    #[test]
+    fn it_constrains() {
+        let constraint = arg(0).le(1000).and(arg(1).eq("AUD"));
+        let method = Method::new("transfer")
+            .constrain(constraint.clone())
+            .unwrap();
+
+        assert_eq!(method.constraints, Some(constraint.to_bytes().unwrap()));
+        assert_eq!(
+            method.get_pact().map(|pact| Constraint::decompile(&pact)),
+            Some(Ok(constraint))
+        );
+    }
+
+    #[test]
+    fn it_describes_constraints() {
+        let method = Method::new("transfer")
+            .constrain(arg(0).le(1000).and(arg(1).eq("AUD").or(arg(1).eq("NZD"))))
+            .unwrap();
+
+        assert_eq!(
+            method.describe_constraints().as_deref(),
+            Some(r#"arg0 <= 1000 && (arg1 == "AUD" || arg1 == "NZD")"#)
+        );
+        assert_eq!(Method::new("transfer").describe_constraints(), None);
+        assert_eq!(
+            Method::new("transfer")
+                .constraints(vec![0x55; 2])
+                .describe_constraints()
+                .as_deref(),
+            Some("invalid constraints 0x5555")
+        );
+    }
+
+    #[test]
+    fn it_displays() {
+        let method = Method::new("transfer")
+            .block_cooldown(10)
+            .constrain(arg(0).le(1000))
+            .unwrap();
+
+        assert_eq!(
+            method.to_string(),
+            "method transfer cooldown 10 where arg0 <= 1000"
+        );
+        assert_eq!(Method::new("burn").deny().to_string(), "deny method burn");
+        assert_eq!(
+            Method::new("a b").constraints(vec![0x01]).to_string(),
+            r#"method "a b" constraints 0x01"#
+        );
+        assert_eq!(
+            format!("{:#}", Method::new("transfer_*")),
+            "method transfer_* (pattern)"
+        );
+        assert_eq!(format!("{:#}", Method::new("*")), "method * (wildcard)");
+        assert_eq!(format!("{:#}", Method::new("transfer")), "method transfer");
+    }
+
+    #[test]
+    fn it_does_not_constrain_with_invalid_constraints() {
+        assert_eq!(
+            Method::new("transfer").constrain(arg(0).lt("AUD")),
+            Err(ConstraintError::TypeMismatch)
+        );
+        assert_eq!(
+            Method::new("transfer").constrain(arg(0).eq("a".repeat(256))),
+            Err(ConstraintError::ValueTooLong)
+        );
+    }
+
     #[test]
     fn bad_constraints_are_none() {
         let method = Method::new("TestMethod").constraints(vec![0x55; 9]);
@@ -229,7 +524,7 @@ mod test {
 
     #[test]
     fn it_encodes_up_to_256_constraints_bytes() {
-        let method = Method::new("TestMethod").constraints(vec![0x55; 300]);
+        let method = Method::new("TestMethod").constraints(vec![0x55; 256]);
 
         let expected_name = String::from("TestMethod").into_bytes();
         let remainder = vec![0x00_u8; 32_usize - expected_name.len()];
@@ -245,6 +540,32 @@ mod test {
         assert_eq!(method.encode(), expected);
     }
 
+    #[test]
+    fn it_encodes_more_than_256_constraints_bytes_with_a_u16_length() {
+        let method = Method::new("TestMethod").constraints(vec![0x55; 300]);
+
+        let expected_name = String::from("TestMethod").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - expected_name.len()];
+        let expected: Vec<u8> = [
+            vec![CONSTRAINTS_MASK | LONG_CONSTRAINTS_MASK],
+            expected_name,
+            remainder,
+            300_u16.to_le_bytes().to_vec(),
+            vec![0x55; 300],
+        ]
+        .concat();
+
+        assert_eq!(method.encode(), expected);
+    }
+
+    #[test]
+    fn it_does_not_encode_more_than_max_constraints_bytes() {
+        let method = Method::new("TestMethod").constraints(vec![0x55; MAX_CONSTRAINTS + 1]);
+
+        assert_eq!(method.try_encode(), Err(EncodeError::ConstraintsTooLong));
+        assert_eq!(method.encode(), Vec::<u8>::default());
+    }
+
     #[test]
     fn it_does_not_encode_constraints_with_0_length() {
         let method = Method::new("TestMethod").constraints(vec![0x55; 0]);
@@ -397,4 +718,172 @@ mod test {
         assert_eq!(method.block_cooldown, Some(0x08040201));
         assert_eq!(method.constraints, Some(vec![0x00; 33]));
     }
+
+    // Strict Decoding Tests
+    #[test]
+    fn it_decodes_strict() {
+        let method = Method::new("TestMethod")
+            .block_cooldown(0x08040201)
+            .constraints(vec![0x00; 33]);
+
+        assert_eq!(Method::decode_strict(&method.encode()), Ok(method));
+    }
+
+    #[test]
+    fn it_decodes_constraints_with_either_length() {
+        for length in [256, 257, MAX_CONSTRAINTS] {
+            let method = Method::new("TestMethod").constraints(vec![0x00; length]);
+
+            assert_eq!(Method::decode_strict(&method.encode()), Ok(method));
+        }
+    }
+
+    #[test]
+    fn decode_fails_with_more_than_max_constraints_bytes() {
+        let name_bytes = String::from("TestMethod").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        let length = u16::try_from(MAX_CONSTRAINTS + 1).unwrap();
+        let encoded: Vec<u8> = [
+            vec![CONSTRAINTS_MASK | LONG_CONSTRAINTS_MASK],
+            name_bytes,
+            remainder,
+            length.to_le_bytes().to_vec(),
+            vec![0x00; MAX_CONSTRAINTS + 1],
+        ]
+        .concat();
+
+        assert_eq!(
+            Method::decode(&mut &encoded[..]),
+            Err(codec::Error::from("constraints are too long"))
+        );
+    }
+
+    #[test]
+    fn it_encodes_and_decodes_constraints_within_limits() {
+        let method = Method::new("TestMethod").constraints(vec![0x00; MAX_CONSTRAINTS + 1]);
+        let limits = Limits::default().max_constraints(MAX_CONSTRAINTS * 2);
+        let encoded = method.try_encode_limited(limits).unwrap();
+
+        assert_eq!(
+            Method::decode_limited(&mut &encoded[..], limits),
+            Ok(method.clone())
+        );
+        assert_eq!(
+            Method::decode(&mut &encoded[..]),
+            Err(codec::Error::from("constraints are too long"))
+        );
+
+        let limits = Limits::default().max_constraints(256);
+        let encoded = method.constraints(vec![0x00; 257]).encode();
+        assert_eq!(
+            Method::decode_limited(&mut &encoded[..], limits),
+            Err(codec::Error::from("constraints are too long"))
+        );
+    }
+
+    #[test]
+    fn encode_fails_with_constraints_beyond_limits() {
+        let method = Method::new("TestMethod").constraints(vec![0x00; 257]);
+        let limits = Limits::default().max_constraints(256);
+
+        assert_eq!(
+            method.try_encode_limited(limits),
+            Err(EncodeError::ConstraintsTooLong)
+        );
+        assert!(method.try_encode().is_ok());
+    }
+
+    #[test]
+    fn decode_strict_fails_with_a_needlessly_long_constraints_length() {
+        let name_bytes = String::from("TestMethod").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        let encoded: Vec<u8> = [
+            vec![CONSTRAINTS_MASK | LONG_CONSTRAINTS_MASK],
+            name_bytes,
+            remainder,
+            33_u16.to_le_bytes().to_vec(),
+            vec![0x00; 33],
+        ]
+        .concat();
+
+        assert_eq!(
+            Method::decode(&mut &encoded[..]).map(|m| m.constraints),
+            Ok(Some(vec![0x00; 33]))
+        );
+        assert_eq!(
+            Method::decode_strict(&encoded),
+            Err(codec::Error::from("non-canonical encoding"))
+        );
+    }
+
+    #[test]
+    fn it_does_not_encode_or_decode_deny() {
+        let method = Method::new("force_transfer").deny();
+
+        assert_eq!(
+            method.try_encode(),
+            Err(EncodeError::MethodDenyUnsupported {
+                method: "force_transfer".to_string()
+            })
+        );
+        assert_eq!(method.encode(), Vec::<u8>::default());
+
+        // Decoders ignoring the flag would grant the method, so it is never read as a grant
+        let name = String::from("force_transfer").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - name.len()];
+        let encoded: Vec<u8> = [vec![DENY_MASK], name, remainder].concat();
+        let error = Err(codec::Error::from(
+            "deny entries are only defined for version 1",
+        ));
+        assert_eq!(Method::decode(&mut &encoded[..]), error);
+        assert_eq!(Method::decode_strict(&encoded), error);
+    }
+
+    #[test]
+    fn decode_strict_fails_with_undefined_flags() {
+        let name_bytes = String::from("TestMethod").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        let encoded: Vec<u8> = [vec![0x08_u8], name_bytes, remainder].concat();
+
+        assert!(Method::decode(&mut &encoded[..]).is_ok());
+        assert_eq!(
+            Method::decode_strict(&encoded),
+            Err(codec::Error::from("undefined method flags"))
+        );
+    }
+
+    #[test]
+    fn decode_strict_fails_with_junk_after_the_name() {
+        let name_bytes = String::from("TestMethod").into_bytes();
+        let mut remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        remainder[4] = b'x';
+        let encoded: Vec<u8> = [vec![0_u8], name_bytes, remainder].concat();
+
+        assert!(Method::decode(&mut &encoded[..]).is_ok());
+        assert_eq!(
+            Method::decode_strict(&encoded),
+            Err(codec::Error::from("method names should be zero padded"))
+        );
+    }
+
+    #[test]
+    fn decode_strict_fails_with_trailing_bytes() {
+        let encoded: Vec<u8> = [Method::new("TestMethod").encode(), vec![0]].concat();
+
+        assert_eq!(
+            Method::decode_strict(&encoded),
+            Err(codec::Error::from("unexpected trailing bytes"))
+        );
+    }
+
+    #[test]
+    fn decode_strict_fails_with_leading_nul_in_the_name() {
+        let encoded: Vec<u8> = [vec![0_u8, 0_u8], vec![b'a'; 31]].concat();
+
+        assert!(Method::decode(&mut &encoded[..]).is_ok());
+        assert_eq!(
+            Method::decode_strict(&encoded),
+            Err(codec::Error::from("method names should be zero padded"))
+        );
+    }
 }
@@ -6,33 +6,73 @@
 //!
 
 use super::method::Method;
+use crate::encode::{EncodeError, TryEncode};
+use crate::name::{deserialize_module_name, ModuleName, NameError};
+use crate::{ensure_canonical, is_zero_padded, limits::Limits, LimitedDecode, StrictDecode};
 use crate::trnnut::MAX_METHODS;
-use super::WILDCARD;
+use crate::pattern::{most_specific, pattern_marker, specificity};
+use crate::policy::write_name;
 use alloc::{
+    fmt::{self, Display, Formatter},
     string::{String, ToString},
     vec::Vec,
 };
 use codec::{Decode, Encode, Input, Output};
 use core::convert::TryFrom;
-const BLOCK_COOLDOWN_MASK: u8 = 0b0000_0001;
+pub(crate) const BLOCK_COOLDOWN_MASK: u8 = 0b0000_0001;
 
 /// A TRN permission domain module
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Module {
+    #[serde(deserialize_with = "deserialize_module_name")]
     pub name: String,
     pub block_cooldown: Option<u32>,
     pub methods: Vec<Method>,
+    /// Denies every method of the module instead of granting `methods`
+    #[serde(default)]
+    pub deny: bool,
 }
 
 impl Module {
+    /// Creates a Module named `name`, see `try_new` for names which may be invalid
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid `ModuleName`, see `unbounded` for `TRNNutV1` names
     pub fn new(name: &str) -> Self {
+        Self::try_new(name).unwrap_or_else(|err| panic!("invalid module name {name:?}: {err}"))
+    }
+
+    /// Creates a Module without validating `name`, for `TRNNutV1`, which encodes names
+    /// of any length. Version 0 rejects names which are not a valid `ModuleName` when encoding
+    pub fn unbounded(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            block_cooldown: None,
+            methods: Vec::new(),
+            deny: false,
+        }
+    }
+
+    /// Creates a Module with a validated name
+    pub fn named(name: ModuleName) -> Self {
         Self {
             name: name.into(),
             block_cooldown: None,
             methods: Vec::new(),
+            deny: false,
         }
     }
 
+    /// Creates a Module, if `name` is a valid `ModuleName`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NameError` if `name` would not encode intact
+    pub fn try_new(name: &str) -> Result<Self, NameError> {
+        Ok(Self::named(ModuleName::try_from(name)?))
+    }
+
     pub fn block_cooldown(mut self, block_cooldown: u32) -> Self {
         self.block_cooldown = Some(block_cooldown);
         self
@@ -43,40 +83,136 @@ impl Module {
         self
     }
 
+    pub fn deny(mut self) -> Self {
+        self.deny = true;
+        self
+    }
+
     /// Returns the method, if it exists in the Module
-    /// Wildcard methods have lower priority than defined methods
+    /// Exact names have priority over patterns, then longer pattern prefixes, then suffixes
+    /// Deny entries are not returned, see `denies`
     pub fn get_method(&self, method: &str) -> Option<&Method> {
-        let mut outcome: Option<&Method> = None;
-        for m in &self.methods {
-            if m.name == method {
-                outcome = Some(m);
-                break;
-            } else if m.name == WILDCARD {
-                outcome = Some(m);
+        most_specific(self.methods.iter().filter(|m| !m.deny), method, |m| {
+            m.name.as_str()
+        })
+    }
+
+    /// Returns whether any deny entry of the Module matches `method`
+    /// Deny entries are checked before, and override, every granting entry
+    pub fn denies(&self, method: &str) -> bool {
+        self.find_deny(method).is_some()
+    }
+
+    /// Returns the first deny entry of the Module matching `method`
+    pub(crate) fn find_deny(&self, method: &str) -> Option<&Method> {
+        self.methods
+            .iter()
+            .find(|m| m.deny && specificity(&m.name, method).is_some())
+    }
+
+    /// Returns the method granted by the Module, unless it is denied
+    pub(crate) fn granted_method(&self, method: &str) -> Option<&Method> {
+        if self.denies(method) {
+            None
+        } else {
+            self.get_method(method)
+        }
+    }
+
+    /// Writes the Module and then each method on its own line, indented by `indent`
+    pub(crate) fn fmt_lines(&self, f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}", "")?;
+        if self.deny {
+            write!(f, "deny ")?;
+        }
+        write!(f, "module ")?;
+        write_name(f, &self.name)?;
+        if let Some(marker) = pattern_marker(&self.name) {
+            write!(f, " ({marker})")?;
+        }
+        if let Some(cooldown) = self.block_cooldown {
+            write!(f, " cooldown {cooldown}")?;
+        }
+        for method in &self.methods {
+            write!(f, "\n{:1$}{method:#}", "", indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders on one line, e.g. `module Balances cooldown 10 { method transfer; deny method burn }`,
+/// as in the policy language but with methods separated by `;`
+/// The alternate form, `{:#}`, renders each method on its own line
+impl Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_lines(f, 0);
+        }
+        if self.deny {
+            write!(f, "deny ")?;
+        }
+        write!(f, "module ")?;
+        write_name(f, &self.name)?;
+        if let Some(cooldown) = self.block_cooldown {
+            write!(f, " cooldown {cooldown}")?;
+        }
+        if self.methods.is_empty() {
+            return write!(f, " {{}}");
+        }
+        write!(f, " {{ ")?;
+        for (i, method) in self.methods.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
             }
+            write!(f, "{method}")?;
         }
-        outcome
+        write!(f, " }}")
     }
 }
 
-impl Encode for Module {
-    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
-        if self.methods.is_empty() || self.methods.len() > MAX_METHODS {
-            return;
+impl TryEncode for Module {
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError> {
+        // The method count and cooldown flag leave no spare bit to mark a denied module
+        if self.deny {
+            return Err(EncodeError::ModuleDenyUnsupported {
+                module: self.name.clone(),
+            });
         }
-        let method_count = u8::try_from(self.methods.len() - 1);
-        if method_count.is_err() {
-            return;
+        if self.methods.is_empty() {
+            return Err(EncodeError::NoMethods {
+                module: self.name.clone(),
+            });
         }
-        let mut method_count_and_has_cooldown_byte = method_count.unwrap() << 1;
+        if self.methods.len() > MAX_METHODS {
+            return Err(EncodeError::TooManyMethods {
+                module: self.name.clone(),
+            });
+        }
+        ModuleName::try_from(self.name.as_str())?;
+        let method_count = u8::try_from(self.methods.len() - 1).map_err(|_| {
+            EncodeError::TooManyMethods {
+                module: self.name.clone(),
+            }
+        })?;
+
+        // Encode all methods before modifying the output buffer
+        let mut method_payload_buf: Vec<u8> = Vec::<u8>::default();
+        for method in &self.methods {
+            method.try_encode_limited_to(&mut method_payload_buf, limits)?;
+        }
+
+        let mut method_count_and_has_cooldown_byte = method_count << 1;
         if self.block_cooldown.is_some() {
             method_count_and_has_cooldown_byte |= BLOCK_COOLDOWN_MASK;
         }
         buf.push_byte(method_count_and_has_cooldown_byte);
 
         let mut name = [0_u8; 32];
-        let length = 32.min(self.name.len());
-        name[0..length].clone_from_slice(&self.name.as_bytes()[0..length]);
+        name[0..self.name.len()].clone_from_slice(self.name.as_bytes());
 
         buf.write(&name);
 
@@ -86,14 +222,26 @@ impl Encode for Module {
             }
         }
 
-        for method in &self.methods {
-            method.encode_to(buf);
-        }
+        buf.write(method_payload_buf.as_slice());
+
+        Ok(())
     }
 }
 
-impl Decode for Module {
-    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+impl Encode for Module {
+    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+        // Failures are reported by `TryEncode`, nothing is written here
+        let _ = self.try_encode_to(buf);
+    }
+}
+
+impl Module {
+    /// Decode a module within `limits`, when `strict` non-zero name padding is rejected
+    pub(crate) fn decode_with<I: Input>(
+        input: &mut I,
+        strict: bool,
+        limits: Limits,
+    ) -> Result<Self, codec::Error> {
         let block_cooldown_and_method_count: u8 = input.read_byte()?;
         let method_count = (block_cooldown_and_method_count >> 1) + 1;
 
@@ -101,6 +249,9 @@ impl Decode for Module {
         input
             .read(&mut name_buf)
             .map_err(|_| "expected 32 byte module name")?;
+        if strict && !is_zero_padded(&name_buf) {
+            return Err(codec::Error::from("module names should be zero padded"));
+        }
         let name = core::str::from_utf8(&name_buf)
             .map_err(|_| codec::Error::from("module names should be utf8 encoded"))?
             .trim_matches(char::from(0))
@@ -121,7 +272,7 @@ impl Decode for Module {
         let mut methods: Vec<Method> = Vec::default();
 
         for _ in 0..method_count {
-            let m: Method = Decode::decode(input)?;
+            let m = Method::decode_with(input, strict, limits)?;
             methods.push(m);
         }
 
@@ -129,13 +280,38 @@ impl Decode for Module {
             name,
             block_cooldown: module_cooldown,
             methods,
+            deny: false,
         })
     }
 }
 
+impl Decode for Module {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::decode_with(input, false, Limits::default())
+    }
+}
+
+impl LimitedDecode for Module {
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error> {
+        Self::decode_with(input, false, limits)
+    }
+}
+
+impl StrictDecode for Module {
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error> {
+        let mut remaining = input;
+        let module = Self::decode_with(&mut remaining, true, Limits::default())?;
+        ensure_canonical(module, input, remaining)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{Method, Module, BLOCK_COOLDOWN_MASK};
+    use crate::method::MAX_CONSTRAINTS;
+    use crate::encode::{EncodeError, TryEncode};
+    use crate::name::NameError;
+    use crate::StrictDecode;
     use codec::{Decode, Encode};
     use std::assert_eq;
 
@@ -157,6 +333,25 @@ mod test {
         assert_eq!(module.methods, vec![]);
     }
 
+    #[test]
+    fn it_validates_names() {
+        assert_eq!(Module::try_new("TestModule"), Ok(Module::new("TestModule")));
+        assert_eq!(
+            Module::try_new("I don't like green eggs and ham, I don't like you Sam I am;"),
+            Err(NameError::TooLong(59))
+        );
+        assert!(serde_json::from_str::<Module>(
+            r#"{"name":"Test\u0000Module","block_cooldown":null,"methods":[]}"#
+        )
+        .is_err());
+        assert_eq!(
+            Module::unbounded("Test\0Module")
+                .methods(vec![Method::new("TestMethod")])
+                .try_encode(),
+            Err(EncodeError::NameContainsNul)
+        );
+    }
+
     // Encoding Tests
     #[test]
     fn it_encodes() {
@@ -178,16 +373,59 @@ mod test {
     #[test]
     fn it_does_not_encode_without_methods() {
         let module = Module::new("TestModule");
+        assert_eq!(
+            module.try_encode(),
+            Err(EncodeError::NoMethods {
+                module: "TestModule".into()
+            })
+        );
         assert_eq!(module.encode(), Vec::<u8>::default());
     }
 
     #[test]
-    fn it_encodes_only_32_characters_for_name() {
-        let module = Module::new("I don't like green eggs and ham, I don't like you Sam I am;")
+    fn it_does_not_encode_a_denied_module() {
+        let module = Module::new("TestModule").deny();
+        assert_eq!(
+            module.try_encode(),
+            Err(EncodeError::ModuleDenyUnsupported {
+                module: "TestModule".into()
+            })
+        );
+        assert_eq!(module.encode(), Vec::<u8>::default());
+    }
+
+    #[test]
+    fn it_gets_methods_unless_denied() {
+        let module = Module::new("TestModule").methods(vec![
+            Method::new("*"),
+            Method::new("force_*").deny(),
+            Method::new("force_transfer"),
+        ]);
+
+        assert_eq!(module.get_method("force_transfer"), Some(&module.methods[2]));
+        assert!(module.denies("force_transfer"));
+        assert_eq!(module.granted_method("force_transfer"), None);
+        assert_eq!(module.granted_method("transfer"), Some(&module.methods[0]));
+    }
+
+    #[test]
+    fn it_does_not_encode_names_longer_than_32_bytes() {
+        let module = Module::unbounded("I don't like green eggs and ham, I don't like you Sam I am;")
             .methods(methods!("TestMethod"));
-        let expected_length = 33 + 33;
 
-        assert_eq!(module.encode().len(), expected_length);
+        assert_eq!(module.try_encode(), Err(EncodeError::NameTooLong));
+        assert_eq!(module.encode(), Vec::<u8>::default());
+    }
+
+    #[test]
+    fn it_does_not_encode_with_an_invalid_method() {
+        let module = Module::new("TestModule").methods(vec![
+            Method::new("TestMethod"),
+            Method::new("TestMethod").constraints(vec![0x55; MAX_CONSTRAINTS + 1]),
+        ]);
+
+        assert_eq!(module.try_encode(), Err(EncodeError::ConstraintsTooLong));
+        assert_eq!(module.encode(), Vec::<u8>::default());
     }
 
     #[test]
@@ -337,4 +575,71 @@ mod test {
         assert_eq!(module.methods[5].name, "Sam");
         assert_eq!(module.methods[6].name, "I am");
     }
+
+    // Strict Decoding Tests
+    #[test]
+    fn it_decodes_strict() {
+        let module = Module::new("TestModule")
+            .methods(methods!("I", "do", "not"))
+            .block_cooldown(0x10204080);
+
+        assert_eq!(Module::decode_strict(&module.encode()), Ok(module));
+    }
+
+    #[test]
+    fn decode_strict_fails_with_junk_after_the_name() {
+        let name_bytes = String::from("TestModule").into_bytes();
+        let mut remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        remainder[1] = b'x';
+        let encoded: Vec<u8> = [
+            vec![0_u8],
+            name_bytes,
+            remainder,
+            Method::new("TestMethod").encode(),
+        ]
+        .concat();
+
+        assert!(Module::decode(&mut &encoded[..]).is_ok());
+        assert_eq!(
+            Module::decode_strict(&encoded),
+            Err(codec::Error::from("module names should be zero padded"))
+        );
+    }
+
+    #[test]
+    fn decode_strict_fails_with_undefined_method_flags() {
+        let name_bytes = String::from("TestModule").into_bytes();
+        let remainder = vec![0x00_u8; 32_usize - name_bytes.len()];
+        let mut method = Method::new("TestMethod").encode();
+        method[0] = 0x80;
+        let encoded: Vec<u8> = [vec![0_u8], name_bytes, remainder, method].concat();
+
+        assert!(Module::decode(&mut &encoded[..]).is_ok());
+        assert_eq!(
+            Module::decode_strict(&encoded),
+            Err(codec::Error::from("undefined method flags"))
+        );
+    }
+
+    #[test]
+    fn it_displays() {
+        let module = Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer_*"), Method::new("burn").deny()]);
+
+        assert_eq!(
+            module.to_string(),
+            "module Balances cooldown 10 { method transfer_*; deny method burn }"
+        );
+        assert_eq!(
+            format!("{module:#}"),
+            "module Balances cooldown 10\n  method transfer_* (pattern)\n  deny method burn"
+        );
+        assert_eq!(Module::new("*").deny().to_string(), "deny module * {}");
+        assert_eq!(
+            Module::new("method").deny().to_string(),
+            r#"deny module "method" {}"#
+        );
+        assert_eq!(format!("{:#}", Module::new("*")), "module * (wildcard)");
+    }
 }
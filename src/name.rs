@@ -0,0 +1,199 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Name
+//!
+//! Validated module and method names of TRNNut for use in TRN
+//!
+//! Version 0 encodes names in 32 zero padded bytes, so a longer name would be
+//! cut, possibly within a UTF-8 character, and a NUL byte would end it early.
+//! `ModuleName` and `MethodName` reject such names when they are created, and
+//! `Module` and `Method` names are validated by their builders and when deserialized.
+//! Version 1 prefixes names with their length, so `TRNNutV1` leaves them unbounded.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::String,
+};
+use core::{convert::TryFrom, ops::Deref};
+use serde::{Deserialize, Deserializer};
+
+/// The maximum length of a module or method name in bytes
+pub const MAX_NAME_BYTES: usize = 32;
+
+/// Error which may occur while validating a module or method name
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NameError {
+    /// The name is longer than `MAX_NAME_BYTES`, with its length in bytes
+    TooLong(usize),
+    /// The name contains a NUL byte, which would end it early once encoded
+    NulByte,
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLong(len) => {
+                write!(f, "name is {len} bytes, longer than {MAX_NAME_BYTES} bytes")
+            }
+            Self::NulByte => write!(f, "name contains a NUL byte"),
+        }
+    }
+}
+
+/// Checks that `name` encodes without being cut or ended early
+fn validate(name: &str) -> Result<(), NameError> {
+    if name.len() > MAX_NAME_BYTES {
+        return Err(NameError::TooLong(name.len()));
+    }
+    if name.contains('\0') {
+        return Err(NameError::NulByte);
+    }
+    Ok(())
+}
+
+macro_rules! name_type {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(
+            Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize,
+        )]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = NameError;
+
+            fn try_from(name: &str) -> Result<Self, Self::Error> {
+                validate(name)?;
+                Ok(Self(name.into()))
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = NameError;
+
+            fn try_from(name: String) -> Result<Self, Self::Error> {
+                validate(&name)?;
+                Ok(Self(name))
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(name: $name) -> Self {
+                name.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+name_type!(
+    /// A module name, or pattern, which fits the TRNNut encoding
+    ModuleName
+);
+
+name_type!(
+    /// A method name, or pattern, which fits the TRNNut encoding
+    MethodName
+);
+
+/// Deserializes a `Module` name, rejecting names which are not a valid `ModuleName`
+pub(crate) fn deserialize_module_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    ModuleName::deserialize(deserializer).map(ModuleName::into_string)
+}
+
+/// Deserializes a `Method` name, rejecting names which are not a valid `MethodName`
+pub(crate) fn deserialize_method_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    MethodName::deserialize(deserializer).map(MethodName::into_string)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MethodName, ModuleName, NameError, MAX_NAME_BYTES};
+    use core::convert::TryFrom;
+
+    #[test]
+    fn it_accepts_names_up_to_32_bytes() {
+        let name = "a".repeat(MAX_NAME_BYTES);
+
+        assert_eq!(
+            ModuleName::try_from(name.as_str()).map(String::from),
+            Ok(name)
+        );
+        assert_eq!(
+            MethodName::try_from("transfer_*").as_deref(),
+            Ok("transfer_*")
+        );
+    }
+
+    #[test]
+    fn it_rejects_names_longer_than_32_bytes() {
+        assert_eq!(
+            ModuleName::try_from("a".repeat(33)),
+            Err(NameError::TooLong(33))
+        );
+        // 31 ASCII bytes and a 2 byte character would be cut within the character
+        let split = format!("{}é", "a".repeat(31));
+        assert_eq!(
+            MethodName::try_from(split.as_str()),
+            Err(NameError::TooLong(33))
+        );
+    }
+
+    #[test]
+    fn it_rejects_nul_bytes() {
+        assert_eq!(MethodName::try_from("trans\0fer"), Err(NameError::NulByte));
+    }
+
+    #[test]
+    fn it_validates_when_deserializing() {
+        assert_eq!(
+            serde_json::from_str::<ModuleName>(r#""Balances""#)
+                .map(String::from)
+                .ok(),
+            Some("Balances".into())
+        );
+        let error = serde_json::from_str::<MethodName>(&format!(r#""{}""#, "a".repeat(33)))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "name is 33 bytes, longer than 32 bytes");
+        assert_eq!(
+            serde_json::to_string(&MethodName::try_from("transfer").unwrap()).unwrap(),
+            r#""transfer""#
+        );
+    }
+}
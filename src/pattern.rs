@@ -0,0 +1,288 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Pattern
+//!
+//! Module and method name patterns of TRNNut for use in TRN
+//!
+//! A name with a single `*` is a pattern matching any name which starts with
+//! the text before the `*` and ends with the text after it, e.g. `transfer_*`,
+//! `*_keep_alive` or `nft::*`. `WILDCARD` is the pattern matching every name.
+//!
+
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+
+use crate::WILDCARD;
+
+/// How specifically a module or method name matches a runtime name
+///
+/// Exact names are the most specific, then patterns by the length of their
+/// literal prefix and then of their literal suffix, so `WILDCARD` is the least
+/// specific of all.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Specificity {
+    Pattern { prefix: usize, suffix: usize },
+    Exact,
+}
+
+/// Splits a pattern into the literal text before and after its `*`
+/// Names without exactly one `*` are not patterns
+pub fn split_pattern(pattern: &str) -> Option<(&str, &str)> {
+    let (prefix, suffix) = pattern.split_once('*')?;
+    if suffix.contains('*') {
+        None
+    } else {
+        Some((prefix, suffix))
+    }
+}
+
+/// Returns whether `name` is a pattern rather than an exact name
+pub fn is_pattern(name: &str) -> bool {
+    split_pattern(name).is_some()
+}
+
+/// Returns how specifically `pattern` matches `name`, if it matches at all
+pub fn specificity(pattern: &str, name: &str) -> Option<Specificity> {
+    if pattern == name {
+        return Some(Specificity::Exact);
+    }
+    let (prefix, suffix) = split_pattern(pattern)?;
+    if name.len() >= prefix.len() + suffix.len()
+        && name.starts_with(prefix)
+        && name.ends_with(suffix)
+    {
+        Some(Specificity::Pattern {
+            prefix: prefix.len(),
+            suffix: suffix.len(),
+        })
+    } else {
+        None
+    }
+}
+
+/// Returns how a name which is not exact is marked in displays, if it is not exact
+pub(crate) fn pattern_marker(name: &str) -> Option<&'static str> {
+    if name == WILDCARD {
+        Some("wildcard")
+    } else if is_pattern(name) {
+        Some("pattern")
+    } else {
+        None
+    }
+}
+
+/// Returns the entry whose name most specifically matches `name`
+/// The first of equal exact names wins, and the last of equal patterns
+pub(crate) fn most_specific<'a, T: 'a>(
+    entries: impl IntoIterator<Item = &'a T>,
+    name: &str,
+    entry_name: impl Fn(&T) -> &str,
+) -> Option<&'a T> {
+    most_specific_of(entries, name, |entry: &&T| entry_name(entry))
+}
+
+/// Returns the entry whose name most specifically matches `name`, as `most_specific`
+/// does for entries which are not references, e.g. views of encoded entries
+pub(crate) fn most_specific_of<T>(
+    entries: impl IntoIterator<Item = T>,
+    name: &str,
+    entry_name: impl Fn(&T) -> &str,
+) -> Option<T> {
+    let mut outcome: Option<(Specificity, T)> = None;
+    for entry in entries {
+        if let Some(s) = specificity(entry_name(&entry), name) {
+            if !matches!(&outcome, Some((best, _)) if s < *best) {
+                outcome = Some((s, entry));
+                if s == Specificity::Exact {
+                    break;
+                }
+            }
+        }
+    }
+    outcome.map(|(_, entry)| entry)
+}
+
+/// Returns a set of names covering every way `names` may resolve a runtime name
+///
+/// Patterns are represented by their literal prefix and suffix joined with a NUL,
+/// which no exact name contains, and names too short to fit both literals are
+/// listed explicitly. Use `display_name` to turn a representative back into a name.
+pub(crate) fn representative_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    // Names are collected in order of first appearance, the sets only skip duplicates
+    let mut exact: Vec<&str> = Vec::default();
+    let mut prefixes: Vec<&str> = Vec::from([""]);
+    let mut suffixes: Vec<&str> = Vec::from([""]);
+    let mut seen_exact = BTreeSet::new();
+    let mut seen_prefixes = BTreeSet::from([""]);
+    let mut seen_suffixes = BTreeSet::from([""]);
+    for name in names {
+        match split_pattern(name) {
+            Some((prefix, suffix)) => {
+                if seen_prefixes.insert(prefix) {
+                    prefixes.push(prefix);
+                }
+                if seen_suffixes.insert(suffix) {
+                    suffixes.push(suffix);
+                }
+            }
+            None => {
+                if seen_exact.insert(name) {
+                    exact.push(name);
+                }
+            }
+        }
+    }
+
+    let mut representatives: Vec<String> = exact.iter().map(|name| (*name).into()).collect();
+    let mut seen: BTreeSet<String> = representatives.iter().cloned().collect();
+    let mut push = |name: String| {
+        if seen.insert(name.clone()) {
+            representatives.push(name);
+        }
+    };
+    for prefix in &prefixes {
+        for suffix in &suffixes {
+            for overlap in 1..=prefix.len().min(suffix.len()) {
+                if suffix.is_char_boundary(overlap) && prefix.ends_with(&suffix[..overlap]) {
+                    push([prefix, &suffix[overlap..]].concat());
+                }
+            }
+        }
+    }
+    // patterns last, so the broadest representative, `WILDCARD`, comes at the end
+    for prefix in prefixes.iter().rev() {
+        for suffix in suffixes.iter().rev() {
+            push([prefix, "\0", suffix].concat());
+        }
+    }
+    representatives
+}
+
+/// Returns the module or method name of a representative from `representative_names`
+pub(crate) fn display_name(representative: &str) -> String {
+    representative.replacen('\0', WILDCARD, 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        display_name, is_pattern, most_specific, representative_names, specificity, Specificity,
+    };
+
+    #[test]
+    fn it_matches_patterns() {
+        assert_eq!(
+            specificity("transfer", "transfer"),
+            Some(Specificity::Exact)
+        );
+        assert_eq!(specificity("transfer", "transfer_all"), None);
+        assert_eq!(
+            specificity("transfer_*", "transfer_all"),
+            Some(Specificity::Pattern {
+                prefix: 9,
+                suffix: 0
+            })
+        );
+        assert_eq!(
+            specificity("*_keep_alive", "transfer_keep_alive"),
+            Some(Specificity::Pattern {
+                prefix: 0,
+                suffix: 11
+            })
+        );
+        assert_eq!(
+            specificity("nft::*", "nft::mint"),
+            Some(Specificity::Pattern {
+                prefix: 5,
+                suffix: 0
+            })
+        );
+        assert_eq!(specificity("nft::*", "nft"), None);
+        assert_eq!(specificity("ab*ba", "aba"), None);
+        assert_eq!(
+            specificity("ab*ba", "abba"),
+            Some(Specificity::Pattern {
+                prefix: 2,
+                suffix: 2
+            })
+        );
+        assert_eq!(
+            specificity("*", "anything"),
+            Some(Specificity::Pattern {
+                prefix: 0,
+                suffix: 0
+            })
+        );
+        assert_eq!(specificity("*", "*"), Some(Specificity::Exact));
+    }
+
+    #[test]
+    fn names_with_many_stars_are_exact_names() {
+        assert!(is_pattern("*"));
+        assert!(is_pattern("transfer_*"));
+        assert!(!is_pattern("transfer"));
+        assert!(!is_pattern("**"));
+        assert_eq!(specificity("a**", "abc"), None);
+        assert_eq!(specificity("a**", "a**"), Some(Specificity::Exact));
+    }
+
+    #[test]
+    fn it_orders_specificity() {
+        let pattern = |prefix, suffix| Specificity::Pattern { prefix, suffix };
+
+        assert!(Specificity::Exact > pattern(usize::MAX, usize::MAX));
+        assert!(pattern(9, 0) > pattern(5, 0));
+        assert!(pattern(1, 0) > pattern(0, 11));
+        assert!(pattern(1, 1) > pattern(1, 0));
+        assert!(pattern(0, 1) > pattern(0, 0));
+    }
+
+    #[test]
+    fn it_finds_the_most_specific_entry() {
+        let entries = [
+            "*",
+            "transfer_*",
+            "*_keep_alive",
+            "transfer_keep_alive",
+            "t*",
+        ];
+        let find = |name| most_specific(&entries, name, |e| e).copied();
+
+        assert_eq!(find("transfer_keep_alive"), Some("transfer_keep_alive"));
+        assert_eq!(find("burn_keep_alive"), Some("*_keep_alive"));
+        assert_eq!(find("transfer_all"), Some("transfer_*"));
+        assert_eq!(find("total"), Some("t*"));
+        assert_eq!(find("burn"), Some("*"));
+    }
+
+    #[test]
+    fn longer_prefixes_win_over_longer_suffixes() {
+        let entries = ["*_keep_alive", "transfer_*", "t*", "*e"];
+        let find = |name| most_specific(&entries, name, |e| e).copied();
+
+        assert_eq!(find("transfer_all_keep_alive"), Some("transfer_*"));
+        assert_eq!(find("take_alive"), Some("t*"));
+        assert_eq!(find("burn_keep_alive"), Some("*_keep_alive"));
+    }
+
+    #[test]
+    fn later_patterns_and_earlier_exact_names_win_ties() {
+        let entries = [("ab*", 1), ("abc", 2), ("*", 3), ("ab*", 4), ("abc", 5)];
+        let find = |name| most_specific(&entries, name, |e| e.0).map(|e| e.1);
+
+        assert_eq!(find("abd"), Some(4));
+        assert_eq!(find("abc"), Some(2));
+        assert_eq!(find("xyz"), Some(3));
+    }
+
+    #[test]
+    fn it_lists_representative_names() {
+        let representatives = representative_names(["transfer", "ab*", "*ba"].into_iter());
+
+        assert_eq!(
+            representatives,
+            vec!["transfer", "aba", "ab\0ba", "ab\0", "\0ba", "\0"]
+        );
+        assert_eq!(display_name("ab\0ba"), "ab*ba");
+        assert_eq!(display_name("\0"), "*");
+    }
+}
@@ -0,0 +1,772 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Policy
+//!
+//! A text policy language for writing TRNNut by hand
+//!
+//! ```text
+//! # Comments run to the end of the line
+//! module Balances cooldown 10 {
+//!     method transfer where arg0 <= 1000 && arg1 == "AUD"
+//!     method transfer_* cooldown 5
+//!     deny method burn
+//! }
+//! deny module Sudo {}
+//! ```
+//!
+//! Only denied modules may be empty. A policy with any deny entry parses into
+//! a `TRNNutV1`, as version 0 can't encode deny entries, and any other policy
+//! into a `TRNNutV0`.
+//!
+//! Constraints compare method arguments `arg0` to `arg15` with `==`, `!=`,
+//! `<`, `<=`, `>`, `>=`, `in` and `not in` against numbers, strings, `0x` hex
+//! bytes, lists or other arguments, joined with `&&`, `||` and parentheses,
+//! see `constraint` for the expressions Pact can evaluate. Constraints which
+//! don't decompile to an expression are written as `constraints 0x…`, which
+//! must be a Pact contract of at most `MAX_CONSTRAINTS` bytes.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter, Write},
+    string::{String, ToString},
+    vec::Vec,
+};
+use pact::types::{Contract as PactContract, Numeric, PactType, StringLike};
+
+use crate::{
+    constraint::{CompareOp, Comparison, Constraint, ConstraintError, Operand},
+    encode::EncodeError,
+    method::{Method, MAX_CONSTRAINTS},
+    module::Module,
+    TRNNut, TRNNutV0, TRNNutV1,
+};
+
+const KEYWORDS: [&str; 8] = [
+    "module",
+    "method",
+    "deny",
+    "cooldown",
+    "where",
+    "constraints",
+    "in",
+    "not",
+];
+
+/// Error which may occur while parsing a policy
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Str(String),
+    Number(u64),
+    Hex(Vec<u8>),
+    Symbol(&'static str),
+    End,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "`{name}`"),
+            Self::Str(string) => write!(f, "{string:?}"),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Hex(_) => write!(f, "hex bytes"),
+            Self::Symbol(symbol) => write!(f, "`{symbol}`"),
+            Self::End => write!(f, "end of input"),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 15] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "{", "}", "(", ")", "[", "]", ",",
+];
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '*' | ':' | '.')
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn error(&self, message: impl Into<String>) -> PolicyError {
+        PolicyError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn string(&mut self) -> Result<String, PolicyError> {
+        let mut string = String::default();
+        loop {
+            let c = self
+                .bump()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            match c {
+                '"' => return Ok(string),
+                '\\' => {
+                    let escaped = match self.bump() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some(c @ ('"' | '\'' | '\\')) => c,
+                        Some('u') if self.bump() == Some('{') => {
+                            let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                            u32::from_str_radix(digits, 16)
+                                .ok()
+                                .filter(|_| self.bump() == Some('}'))
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Returns the next token with its line and column
+    fn next(&mut self) -> Result<(Token, usize, usize), PolicyError> {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek() == Some('#') {
+                self.take_while(|c| c != '\n');
+            } else {
+                break;
+            }
+        }
+        let (line, column) = (self.line, self.column);
+        let rest = &self.src[self.pos..];
+        let token = if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            for _ in 0..symbol.len() {
+                self.bump();
+            }
+            Token::Symbol(symbol)
+        } else if rest.starts_with('"') {
+            self.bump();
+            Token::Str(self.string()?)
+        } else if let Some(digits) = rest.strip_prefix("0x") {
+            let len = digits
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(digits.len());
+            let digits = &digits[..len];
+            for _ in 0..len + 2 {
+                self.bump();
+            }
+            if digits.len() % 2 != 0 {
+                return Err(self.error("hex bytes must have an even number of digits"));
+            }
+            Token::Hex(
+                (0..digits.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| self.error("invalid hex bytes"))?,
+            )
+        } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            Token::Number(
+                digits
+                    .parse()
+                    .map_err(|_| self.error("number is too large"))?,
+            )
+        } else if rest.starts_with(is_name_char) {
+            Token::Name(self.take_while(is_name_char).to_string())
+        } else if let Some(c) = self.peek() {
+            return Err(self.error(alloc::format!("unexpected character {c:?}")));
+        } else {
+            Token::End
+        };
+        Ok((token, line, column))
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> PolicyError {
+        let (_, line, column) = self.tokens[self.pos];
+        PolicyError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> PolicyError {
+        self.error(alloc::format!("expected {expected}, found {}", self.peek()))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Token::Name(name) if name == keyword) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), PolicyError> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&alloc::format!("`{keyword}`")))
+        }
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> bool {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), PolicyError> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&alloc::format!("`{symbol}`")))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, PolicyError> {
+        match self.peek() {
+            Token::Name(name) if !KEYWORDS.contains(&name.as_str()) => {}
+            Token::Str(_) => {}
+            _ => return Err(self.unexpected("a name")),
+        }
+        match self.next() {
+            Token::Name(name) | Token::Str(name) => Ok(name),
+            _ => unreachable!(),
+        }
+    }
+
+    fn cooldown(&mut self) -> Result<Option<u32>, PolicyError> {
+        if !self.keyword("cooldown") {
+            return Ok(None);
+        }
+        match self.peek() {
+            Token::Number(number) => {
+                let cooldown =
+                    u32::try_from(*number).map_err(|_| self.error("cooldown is too large"))?;
+                self.next();
+                Ok(Some(cooldown))
+            }
+            _ => Err(self.unexpected("a cooldown")),
+        }
+    }
+
+    fn arg(&mut self) -> Result<Option<u8>, PolicyError> {
+        let Token::Name(name) = self.peek() else {
+            return Ok(None);
+        };
+        let Some(index) = name
+            .strip_prefix("arg")
+            .filter(|i| !i.is_empty() && i.bytes().all(|b| b.is_ascii_digit()))
+        else {
+            return Ok(None);
+        };
+        let index = index
+            .parse()
+            .map_err(|_| self.error("argument index is too large"))?;
+        self.next();
+        Ok(Some(index))
+    }
+
+    fn value(&mut self) -> Result<PactType, PolicyError> {
+        if self.symbol("[") {
+            let mut items = Vec::default();
+            if !self.symbol("]") {
+                loop {
+                    items.push(self.value()?);
+                    if self.symbol("]") {
+                        break;
+                    }
+                    if !self.symbol(",") {
+                        return Err(self.unexpected("`,` or `]`"));
+                    }
+                }
+            }
+            return Ok(PactType::List(items));
+        }
+        let value = match self.peek() {
+            Token::Number(number) => PactType::Numeric(Numeric(*number)),
+            Token::Str(string) => PactType::StringLike(StringLike(string.as_bytes().to_vec())),
+            Token::Hex(bytes) => PactType::StringLike(StringLike(bytes.clone())),
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.next();
+        Ok(value)
+    }
+
+    fn comparison(&mut self) -> Result<Constraint, PolicyError> {
+        let arg = self.arg()?.ok_or_else(|| self.unexpected("an argument"))?;
+        let op = if self.keyword("in") {
+            CompareOp::In
+        } else if self.keyword("not") {
+            self.expect_keyword("in")?;
+            CompareOp::NotIn
+        } else {
+            let op = match self.peek() {
+                Token::Symbol("==") => CompareOp::Eq,
+                Token::Symbol("!=") => CompareOp::Ne,
+                Token::Symbol("<") => CompareOp::Lt,
+                Token::Symbol("<=") => CompareOp::Le,
+                Token::Symbol(">") => CompareOp::Gt,
+                Token::Symbol(">=") => CompareOp::Ge,
+                _ => return Err(self.unexpected("a comparison")),
+            };
+            self.next();
+            op
+        };
+        let rhs = match self.arg()? {
+            Some(index) => Operand::Arg(index),
+            None => Operand::Value(self.value()?),
+        };
+        Ok(Constraint::Compare(Comparison { arg, op, rhs }))
+    }
+
+    fn atom(&mut self) -> Result<Constraint, PolicyError> {
+        if self.symbol("(") {
+            let constraint = self.expression()?;
+            self.expect_symbol(")")?;
+            Ok(constraint)
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn conjunction(&mut self) -> Result<Constraint, PolicyError> {
+        let mut constraint = self.atom()?;
+        while self.symbol("&&") {
+            constraint = constraint.and(self.atom()?);
+        }
+        Ok(constraint)
+    }
+
+    fn expression(&mut self) -> Result<Constraint, PolicyError> {
+        let mut constraint = self.conjunction()?;
+        while self.symbol("||") {
+            constraint = constraint.or(self.conjunction()?);
+        }
+        Ok(constraint)
+    }
+
+    fn method(&mut self) -> Result<Method, PolicyError> {
+        let deny = self.keyword("deny");
+        self.expect_keyword("method")?;
+        let error = self.error("");
+        let mut method = Method::try_new(&self.name()?).map_err(|e| PolicyError {
+            message: e.to_string(),
+            ..error
+        })?;
+        method.deny = deny;
+        method.block_cooldown = self.cooldown()?;
+        if self.keyword("where") {
+            let error = self.error("");
+            let constraints = self.expression()?.to_bytes().map_err(|e| PolicyError {
+                message: e.to_string(),
+                ..error
+            })?;
+            method.constraints = Some(constraints);
+        } else if self.keyword("constraints") {
+            let error = self.error("");
+            match self.next() {
+                // Only a Pact contract would decode, as it does from JSON
+                Token::Hex(bytes) if bytes.len() > MAX_CONSTRAINTS => {
+                    return Err(PolicyError {
+                        message: ConstraintError::TooLong.to_string(),
+                        ..error
+                    })
+                }
+                Token::Hex(bytes) if PactContract::decode(&bytes).is_err() => {
+                    return Err(PolicyError {
+                        message: "constraints are not a Pact contract".into(),
+                        ..error
+                    })
+                }
+                Token::Hex(bytes) => method.constraints = Some(bytes),
+                _ => return Err(self.unexpected("hex constraints")),
+            }
+        }
+        Ok(method)
+    }
+
+    fn module(&mut self) -> Result<Module, PolicyError> {
+        let deny = self.keyword("deny");
+        self.expect_keyword("module")?;
+        let error = self.error("");
+        let mut module = Module::try_new(&self.name()?).map_err(|e| PolicyError {
+            message: e.to_string(),
+            ..error.clone()
+        })?;
+        module.deny = deny;
+        module.block_cooldown = self.cooldown()?;
+        self.expect_symbol("{")?;
+        while !self.symbol("}") {
+            module.methods.push(self.method()?);
+        }
+        if module.methods.is_empty() && !module.deny {
+            return Err(PolicyError {
+                message: EncodeError::NoMethods {
+                    module: module.name,
+                }
+                .to_string(),
+                ..error
+            });
+        }
+        Ok(module)
+    }
+}
+
+/// Parses a policy into a TRNNut, version 1 if it has deny entries and version 0 otherwise
+///
+/// # Errors
+///
+/// Returns a `PolicyError` locating the first syntax error, module without methods,
+/// or constraints which can't be compiled
+pub fn parse(src: &str) -> Result<TRNNut, PolicyError> {
+    let mut lexer = Lexer {
+        src,
+        pos: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::default();
+    loop {
+        let token = lexer.next()?;
+        let end = token.0 == Token::End;
+        tokens.push(token);
+        if end {
+            break;
+        }
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut modules = Vec::default();
+    while *parser.peek() != Token::End {
+        modules.push(parser.module()?);
+    }
+    let deny = modules
+        .iter()
+        .any(|module| module.deny || module.methods.iter().any(|method| method.deny));
+    if deny {
+        Ok(TRNNut::V1(TRNNutV1 { modules }))
+    } else {
+        Ok(TRNNut::V0(TRNNutV0 { modules }))
+    }
+}
+
+/// Writes a module or method name, quoted unless it reads back as a plain name
+pub(crate) fn write_name<W: Write>(out: &mut W, name: &str) -> fmt::Result {
+    let plain = name.chars().all(is_name_char)
+        && name.starts_with(|c: char| !c.is_ascii_digit())
+        && !KEYWORDS.contains(&name);
+    if plain {
+        out.write_str(name)
+    } else {
+        write!(out, "{name:?}")
+    }
+}
+
+fn write_cooldown<W: Write>(out: &mut W, block_cooldown: Option<u32>) -> fmt::Result {
+    match block_cooldown {
+        Some(cooldown) => write!(out, " cooldown {cooldown}"),
+        None => Ok(()),
+    }
+}
+
+/// Writes the constraints of `method`, if it has any, as an expression if they
+/// decompile to one and as `constraints 0x…` otherwise
+pub(crate) fn write_constraints<W: Write>(out: &mut W, method: &Method) -> fmt::Result {
+    let Some(constraints) = method.constraints.as_ref().filter(|c| !c.is_empty()) else {
+        return Ok(());
+    };
+    let constraint = method
+        .get_pact()
+        .and_then(|pact| Constraint::decompile(&pact).ok())
+        .filter(|c| *c != Constraint::All(Vec::default()));
+    if let Some(constraint) = constraint {
+        return write!(out, " where {constraint}");
+    }
+    out.write_str(" constraints 0x")?;
+    for byte in constraints {
+        write!(out, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// Renders a TRNNut as a policy, which `parse` reads back into the same modules
+/// Constraints compiled by `parse` render the same, others may render as an
+/// equivalent expression
+pub fn render(trnnut: &TRNNut) -> String {
+    let mut out = String::default();
+    for module in trnnut.modules() {
+        if module.deny {
+            out.push_str("deny ");
+        }
+        out.push_str("module ");
+        let _ = write_name(&mut out, &module.name);
+        let _ = write_cooldown(&mut out, module.block_cooldown);
+        if module.methods.is_empty() {
+            out.push_str(" {}\n");
+            continue;
+        }
+        out.push_str(" {\n");
+        for method in &module.methods {
+            out.push_str("    ");
+            if method.deny {
+                out.push_str("deny ");
+            }
+            out.push_str("method ");
+            let _ = write_name(&mut out, &method.name);
+            let _ = write_cooldown(&mut out, method.block_cooldown);
+            let _ = write_constraints(&mut out, method);
+            out.push('\n');
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, render, PolicyError};
+    use crate::{
+        constraint::{CompareOp, Comparison, Constraint, Operand},
+        method::{Method, MAX_CONSTRAINTS},
+        module::Module,
+        TRNNut, TRNNutV0, TRNNutV1, TryEncode,
+    };
+    use pact::types::{Numeric, PactType, StringLike};
+
+    fn constraints(constraint: &Constraint) -> Vec<u8> {
+        constraint.to_bytes().unwrap()
+    }
+
+    fn compare(arg: u8, op: CompareOp, value: PactType) -> Constraint {
+        Constraint::Compare(Comparison {
+            arg,
+            op,
+            rhs: Operand::Value(value),
+        })
+    }
+
+    #[test]
+    fn it_parses_a_policy() {
+        let policy = r#"
+            # Payments
+            module Balances cooldown 10 {
+                method transfer where arg0 <= 1000 && arg1 == "AUD"
+                method transfer_* cooldown 5
+                deny method burn
+            }
+            deny module "Sudo" {}
+            module * { method * }
+        "#;
+        let transfer = compare(0, CompareOp::Le, PactType::Numeric(Numeric(1000))).and(compare(
+            1,
+            CompareOp::Eq,
+            PactType::StringLike(StringLike(b"AUD".to_vec())),
+        ));
+
+        assert_eq!(
+            parse(policy),
+            Ok(TRNNut::V1(TRNNutV1 {
+                modules: vec![
+                    Module::new("Balances").block_cooldown(10).methods(vec![
+                        Method::new("transfer").constraints(constraints(&transfer)),
+                        Method::new("transfer_*").block_cooldown(5),
+                        Method::new("burn").deny(),
+                    ]),
+                    Module::new("Sudo").deny(),
+                    Module::new("*").methods(vec![Method::new("*")]),
+                ]
+            }))
+        );
+        // Only version 1 encodes deny entries
+        assert_eq!(
+            parse("module Balances { method transfer }"),
+            Ok(TRNNut::V0(TRNNutV0 {
+                modules: vec![Module::new("Balances").methods(vec![Method::new("transfer")])]
+            }))
+        );
+    }
+
+    #[test]
+    fn it_parses_expressions() {
+        let policy = r#"module M {
+            method m where (arg0 == 1 || arg0 > 5) && arg1 not in ["a", 0x00ff] && arg2 != arg3
+        }"#;
+        let expected = compare(0, CompareOp::Eq, PactType::Numeric(Numeric(1)))
+            .or(compare(0, CompareOp::Gt, PactType::Numeric(Numeric(5))))
+            .and(compare(
+                1,
+                CompareOp::NotIn,
+                PactType::List(vec![
+                    PactType::StringLike(StringLike(b"a".to_vec())),
+                    PactType::StringLike(StringLike(vec![0x00, 0xff])),
+                ]),
+            ))
+            .and(Constraint::Compare(Comparison {
+                arg: 2,
+                op: CompareOp::Ne,
+                rhs: Operand::Arg(3),
+            }));
+
+        let trnnut = parse(policy).unwrap();
+        assert_eq!(
+            trnnut.modules()[0].methods[0].constraints,
+            Some(constraints(&expected))
+        );
+    }
+
+    #[test]
+    fn it_renders_what_it_parses() {
+        let policy = r#"module Balances cooldown 10 {
+    method transfer where arg0 <= 1000 && arg1 == "AUD\n\u{1b}"
+    method swap where (arg0 == 1 || arg0 > 5) && arg1 not in ["a", 0x00ff]
+    deny method burn cooldown 1
+}
+deny module Sudo {}
+module "module" {
+    method "a b" constraints 0x0000
+    method * where arg0 == 1 || arg1 == arg2
+}
+"#;
+        let trnnut = parse(policy).unwrap();
+
+        assert_eq!(render(&trnnut), policy);
+        assert!(trnnut.try_encode().is_ok());
+        assert_eq!(parse(&render(&trnnut)), Ok(trnnut));
+    }
+
+    #[test]
+    fn it_locates_errors() {
+        let error = |line, column, message: &str| {
+            Err(PolicyError {
+                line,
+                column,
+                message: message.into(),
+            })
+        };
+
+        assert_eq!(
+            parse("module Balances {\n  method transfer where arg0 <=\n}"),
+            error(3, 1, "expected a value, found `}`")
+        );
+        assert_eq!(
+            parse("module Balances {\n  method transfer where arg0 < \"AUD\"\n}"),
+            error(2, 25, "a comparison does not apply to its value")
+        );
+        assert_eq!(
+            parse("module Balances { method transfer where arg0 == 1 || arg1 == 1 && arg2 == 1 }"),
+            error(1, 41, "constraints can't be evaluated left to right")
+        );
+        assert_eq!(
+            parse("module Balances cooldown 4294967296 {}"),
+            error(1, 26, "cooldown is too large")
+        );
+        assert_eq!(
+            parse("module Balances {\n}\ndeny module Sudo {}"),
+            error(1, 8, "module Balances has no methods")
+        );
+        assert_eq!(
+            parse("module method {}"),
+            error(1, 8, "expected a name, found `method`")
+        );
+        assert_eq!(
+            parse("module M { method m where arg0 == \"a }"),
+            error(1, 39, "unterminated string")
+        );
+        assert_eq!(
+            parse("module M { method m"),
+            error(1, 20, "expected `method`, found end of input")
+        );
+        assert_eq!(
+            parse("module M {} ;"),
+            error(1, 13, "unexpected character ';'")
+        );
+        assert_eq!(
+            parse("module M { method \"trans\\0fer\" }"),
+            error(1, 19, "name contains a NUL byte")
+        );
+        assert_eq!(
+            parse("module M { method m constraints 0x010203 }"),
+            error(1, 33, "constraints are not a Pact contract")
+        );
+        let long = format!(
+            "module M {{ method m constraints 0x{} }}",
+            "00".repeat(MAX_CONSTRAINTS + 1)
+        );
+        assert_eq!(
+            parse(&long),
+            error(1, 33, "constraints are longer than 4096 bytes")
+        );
+    }
+}
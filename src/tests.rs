@@ -6,12 +6,15 @@
 #![cfg(test)]
 
 use super::*;
-use crate::method::Method;
+use crate::constraint::{arg, Constraint};
+use crate::cooldown::{CooldownStore, InMemoryCooldownStore};
+use crate::explain::{ConstraintsFailure, MatchKind, MatchedEntry, PactTrace};
+use crate::method::{Method, MAX_CONSTRAINTS};
 use crate::module::Module;
 use crate::trnnut::{MAX_METHODS, MAX_MODULES};
 
-use codec::{Decode, Encode};
-use pact::interpreter::{Comparator, OpCode, OpComp};
+use codec::{Compact, Decode, Encode};
+use pact::interpreter::{Comparator, Conjunction, OpCode, OpComp, OpConj};
 use pact::types::{Contract as PactContract, DataTable};
 use pact::types::{Numeric, PactType, StringLike};
 use std::vec::Vec;
@@ -295,6 +298,63 @@ fn it_works_with_lots_of_things_codec() {
     assert_eq!(trnnut, TRNNutV0::decode(&mut &encoded[..]).unwrap());
 }
 
+#[test]
+fn it_validates_built_constraints() {
+    let method = Method::new("transfer")
+        .constrain(arg(0).le(1000).and(arg(1).is_in(["AUD", "NZD"])))
+        .unwrap();
+    let trnnut = TRNNutV0 {
+        modules: vec![Module::new("Balances").methods(vec![method])],
+    };
+    let trnnut = TRNNutV0::decode(&mut &trnnut.encode()[..]).unwrap();
+    let validate = |amount, currency: &str| {
+        trnnut.validate_module(
+            "Balances",
+            "transfer",
+            &[
+                PactType::Numeric(Numeric(amount)),
+                PactType::StringLike(StringLike(currency.as_bytes().to_vec())),
+            ],
+        )
+    };
+
+    assert_eq!(validate(1000, "AUD"), Ok(()));
+    assert_eq!(validate(1, "NZD"), Ok(()));
+    assert_eq!(
+        validate(1001, "AUD"),
+        Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
+    );
+    assert_eq!(
+        validate(1, "USD"),
+        Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
+    );
+}
+
+#[test]
+fn it_describes_constraints() {
+    let pact = PactContract {
+        data_table: DataTable::new(vec![
+            PactType::Numeric(Numeric(123)),
+            PactType::StringLike(StringLike(b"test".to_vec())),
+        ]),
+        bytecode: [
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x00,
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x11,
+        ]
+        .to_vec(),
+    };
+    let mut constraints: Vec<u8> = Vec::new();
+    pact.encode(&mut constraints);
+    let method = Method::new("method_test").constraints(constraints);
+
+    assert_eq!(
+        method.describe_constraints().as_deref(),
+        Some(r#"arg0 == 123 && arg1 == "test""#)
+    );
+}
+
 #[test]
 fn it_validates_modules() {
     let pact = PactContract {
@@ -440,6 +500,74 @@ fn it_validate_modules_with_empty_constraints() {
     );
 }
 
+#[test]
+fn it_validates_modules_at_a_block_with_cooldowns() {
+    let methods = vec![
+        Method::new("method_test").block_cooldown(10),
+        Method::new("method_test2"),
+    ];
+
+    let module = Module::new("module_test").block_cooldown(5).methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV0 { modules };
+    let mut store = InMemoryCooldownStore::new();
+    let holder = [1_u8; 32];
+
+    assert_eq!(
+        trnnut.validate_module_at(100, &store, &holder, "module_test", "method_test", &[]),
+        Ok(())
+    );
+    store.record_use(&holder, "module_test", "method_test", 100);
+
+    // module cooldown applies to every method of the module
+    assert_eq!(
+        trnnut.validate_module_at(102, &store, &holder, "module_test", "method_test2", &[]),
+        Err(ValidationErr::CooldownActive {
+            remaining_blocks: 3
+        })
+    );
+    assert_eq!(
+        trnnut.validate_module_at(105, &store, &holder, "module_test", "method_test2", &[]),
+        Ok(())
+    );
+    // method cooldown outlasts the module cooldown
+    assert_eq!(
+        trnnut.validate_module_at(105, &store, &holder, "module_test", "method_test", &[]),
+        Err(ValidationErr::CooldownActive {
+            remaining_blocks: 5
+        })
+    );
+    assert_eq!(
+        trnnut.validate_module_at(110, &store, &holder, "module_test", "method_test", &[]),
+        Ok(())
+    );
+    // cooldowns are tracked per holder
+    assert_eq!(
+        trnnut.validate_module_at(101, &store, &[2_u8; 32], "module_test", "method_test", &[]),
+        Ok(())
+    );
+}
+
+#[test]
+fn it_checks_permissions_before_cooldowns() {
+    let method = Method::new("method_test").block_cooldown(10);
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test").methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV0 { modules };
+    let mut store = InMemoryCooldownStore::new();
+    let holder = [1_u8; 32];
+    store.record_use(&holder, "module_test", "method_test", 100);
+
+    assert_eq!(
+        trnnut.validate_module_at(101, &store, &holder, "module_test", "method_test2", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+}
+
 #[test]
 fn it_works_get_pact() {
     // A TRNNut with constraints set
@@ -663,6 +791,7 @@ fn registered_modules_have_priority_over_wildcard_modules() {
 fn it_fails_to_encode_with_zero_modules() {
     let modules: Vec<Module> = Vec::default();
     let trnnut = TRNNutV0 { modules };
+    assert_eq!(trnnut.try_encode(), Err(EncodeError::NoModules));
     assert_eq!(trnnut.encode(), Vec::<u8>::default());
 }
 
@@ -672,6 +801,12 @@ fn it_fails_to_encode_with_zero_methods() {
     let module = Module::new("TestModule").methods(methods);
     let modules = make_modules(&module);
     let trnnut = TRNNutV0 { modules };
+    assert_eq!(
+        trnnut.try_encode(),
+        Err(EncodeError::NoMethods {
+            module: "TestModule".into()
+        })
+    );
     assert_eq!(trnnut.encode(), Vec::<u8>::default());
 }
 
@@ -685,6 +820,7 @@ fn it_fails_to_encode_with_too_many_modules() {
         modules.push(module);
     }
     let trnnut = TRNNutV0 { modules };
+    assert_eq!(trnnut.try_encode(), Err(EncodeError::TooManyModules));
     assert_eq!(trnnut.encode(), Vec::<u8>::default());
 }
 
@@ -698,6 +834,12 @@ fn it_fails_to_encode_with_too_many_methods() {
     let module = Module::new("registered_module").methods(methods);
     let modules = make_modules(&module);
     let trnnut = TRNNutV0 { modules };
+    assert_eq!(
+        trnnut.try_encode(),
+        Err(EncodeError::TooManyMethods {
+            module: "registered_module".into()
+        })
+    );
     assert_eq!(trnnut.encode(), Vec::<u8>::default());
 }
 
@@ -716,9 +858,27 @@ fn it_fails_to_encode_when_trnnut_is_too_large() {
         modules.push(module);
     }
     let trnnut = TRNNutV0 { modules };
+    assert_eq!(
+        trnnut.try_encode(),
+        Err(EncodeError::PayloadTooLarge { size: 141_573 })
+    );
     assert_eq!(trnnut.encode(), Vec::<u8>::default());
 }
 
+#[test]
+fn it_try_encodes_the_same_as_encode() {
+    let method = Method::new("method_test").block_cooldown(123);
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test")
+        .block_cooldown(86_400)
+        .methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV0 { modules };
+    assert_eq!(trnnut.try_encode(), Ok(trnnut.encode()));
+}
+
 #[test]
 fn it_fails_decode_with_invalid_constraints() {
     let encoded_trnnut: Vec<u8> = vec![
@@ -748,3 +908,1192 @@ fn it_fails_decode_with_invalid_constraints() {
         Err(codec::Error::from("invalid constraints codec")),
     );
 }
+
+#[test]
+fn it_works_encode_v1() {
+    let method = Method::new("method_test");
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test").methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV1 { modules };
+
+    assert_eq!(
+        trnnut.encode(),
+        [
+            vec![1, 0, 4, 0, 44],
+            b"module_test".to_vec(),
+            vec![4, 0, 44],
+            b"method_test".to_vec(),
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn it_works_encode_v1_with_cooldowns_and_constraints() {
+    let method = Method::new("method_test")
+        .block_cooldown(123)
+        .constraints(vec![0; 33]);
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test")
+        .block_cooldown(86_400)
+        .methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV1 { modules };
+    let encoded = [
+        vec![1, 0, 4, 1, 44],
+        b"module_test".to_vec(),
+        vec![0x02, 0x46, 0x05, 0x00, 4, 3, 44],
+        b"method_test".to_vec(),
+        vec![0xed, 0x01, 132],
+        vec![0; 33],
+    ]
+    .concat();
+
+    assert_eq!(trnnut.encode(), encoded);
+    assert_eq!(TRNNutV1::decode(&mut &encoded[..]), Ok(trnnut));
+}
+
+#[test]
+fn it_works_v1_codec_with_long_names() {
+    let long_method = "transfer_all_with_a_very_long_extrinsic_name";
+    let methods = vec![
+        Method::unbounded(&[long_method, "_a"].concat()).block_cooldown(1),
+        Method::unbounded(&[long_method, "_b"].concat()).block_cooldown(2),
+    ];
+    let module = Module::unbounded("a_pallet_with_a_name_much_longer_than_32_bytes")
+        .block_cooldown(86_400)
+        .methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV1 { modules };
+    let encoded = trnnut.try_encode().unwrap();
+    let decoded = TRNNutV1::decode(&mut &encoded[..]).unwrap();
+
+    assert_eq!(decoded, trnnut);
+    let module = decoded
+        .get_module("a_pallet_with_a_name_much_longer_than_32_bytes")
+        .expect("module exists");
+    assert_eq!(
+        module
+            .get_method(&[long_method, "_b"].concat())
+            .expect("method exists")
+            .block_cooldown,
+        Some(2)
+    );
+
+    // Only version 0 names are validated when deserialized
+    let json = serde_json::to_string(&trnnut).unwrap();
+    assert_eq!(serde_json::from_str::<TRNNutV1>(&json).ok(), Some(trnnut));
+    let modules = serde_json::to_string(&decoded.modules).unwrap();
+    assert!(serde_json::from_str::<Vec<Module>>(&modules).is_err());
+}
+
+#[test]
+fn it_works_v1_codec_with_lots_of_things() {
+    let methods = vec![
+        Method::new("method_test").block_cooldown(123),
+        Method::new(WILDCARD).block_cooldown(u32::MAX),
+    ];
+    let mut modules: Vec<Module> = Vec::default();
+    for x in 0..MAX_MODULES {
+        modules.push(Module::new(&x.to_string()).methods(methods.clone()));
+    }
+
+    let trnnut = TRNNutV1 { modules };
+    let encoded = trnnut.try_encode().unwrap();
+
+    assert_eq!(TRNNutV1::decode(&mut &encoded[..]), Ok(trnnut));
+}
+
+#[test]
+fn it_validates_modules_v1() {
+    let method = Method::new("method_test");
+    let methods = make_methods(&method);
+
+    let module = Module::new(WILDCARD).methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV1 { modules };
+
+    assert_eq!(
+        trnnut.validate_module("module_test", "method_test", &[]),
+        Ok(())
+    );
+    assert_eq!(
+        trnnut.validate_module("module_test", "method_test2", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+}
+
+#[test]
+fn it_fails_to_encode_v1_with_zero_modules() {
+    let trnnut = TRNNutV1 {
+        modules: Vec::default(),
+    };
+    assert_eq!(trnnut.try_encode(), Err(EncodeError::NoModules));
+    assert_eq!(trnnut.encode(), Vec::<u8>::default());
+}
+
+#[test]
+fn it_fails_decode_v1_with_wrong_version() {
+    let encoded: Vec<u8> = [
+        vec![0, 0, 4, 0, 44],
+        b"module_test".to_vec(),
+        vec![4, 0, 44],
+        b"method_test".to_vec(),
+    ]
+    .concat();
+    assert_eq!(
+        TRNNutV1::decode(&mut &encoded[..]),
+        Err(codec::Error::from("expected version : 1"))
+    );
+}
+
+#[test]
+fn it_fails_decode_v0_with_v1_payload() {
+    let method = Method::new("method_test");
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test").methods(methods);
+    let modules = make_modules(&module);
+
+    let encoded = TRNNutV1 { modules }.encode();
+    assert_eq!(
+        TRNNutV0::decode(&mut &encoded[..]),
+        Err(codec::Error::from("expected version : 0"))
+    );
+}
+
+#[test]
+fn it_fails_decode_v1_with_invalid_counts() {
+    let zero_modules: Vec<u8> = vec![1, 0, 0];
+    let zero_methods: Vec<u8> = [vec![1, 0, 4, 0, 44], b"module_test".to_vec(), vec![0]].concat();
+
+    assert_eq!(
+        TRNNutV1::decode(&mut &zero_modules[..]),
+        Err(codec::Error::from("invalid module count"))
+    );
+    assert_eq!(
+        TRNNutV1::decode(&mut &zero_methods[..]),
+        Err(codec::Error::from("invalid method count"))
+    );
+}
+
+#[test]
+fn it_fails_decode_v1_with_invalid_names_and_constraints() {
+    let bad_module_name: Vec<u8> = [
+        vec![1, 0, 4, 0, 8, 0xf0, 0x28],
+        vec![4, 0, 44],
+        b"method_test".to_vec(),
+    ]
+    .concat();
+    let bad_constraints: Vec<u8> = [
+        vec![1, 0, 4, 0, 44],
+        b"module_test".to_vec(),
+        vec![4, 2, 44],
+        b"method_test".to_vec(),
+        vec![36, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+    ]
+    .concat();
+
+    assert_eq!(
+        TRNNutV1::decode(&mut &bad_module_name[..]),
+        Err(codec::Error::from("module names should be utf8 encoded"))
+    );
+    assert_eq!(
+        TRNNutV1::decode(&mut &bad_constraints[..]),
+        Err(codec::Error::from("invalid constraints codec"))
+    );
+}
+
+#[test]
+fn it_limits_v1_constraints_length() {
+    let method = Method::new("method_test").constraints(vec![0x00; MAX_CONSTRAINTS]);
+    let trnnut = TRNNutV1 {
+        modules: vec![Module::new("module_test").methods(vec![method])],
+    };
+    let encoded = trnnut.try_encode().unwrap();
+    assert_eq!(TRNNutV1::decode_strict(&encoded), Ok(trnnut));
+
+    let method = Method::new("method_test").constraints(vec![0x00; MAX_CONSTRAINTS + 1]);
+    let too_long = TRNNutV1 {
+        modules: vec![Module::new("module_test").methods(vec![method])],
+    };
+    assert_eq!(too_long.try_encode(), Err(EncodeError::ConstraintsTooLong));
+
+    let length = u32::try_from(MAX_CONSTRAINTS + 1).unwrap();
+    let encoded: Vec<u8> = [
+        vec![1, 0, 4, 0, 44],
+        b"module_test".to_vec(),
+        vec![4, 2, 44],
+        b"method_test".to_vec(),
+        Compact(length).encode(),
+        vec![0x00; MAX_CONSTRAINTS + 1],
+    ]
+    .concat();
+    assert_eq!(
+        TRNNutV1::decode(&mut &encoded[..]),
+        Err(codec::Error::from("constraints are too long"))
+    );
+}
+
+#[test]
+fn it_encodes_and_decodes_versioned_trnnuts_within_limits() {
+    let limits = Limits::default().max_constraints(MAX_CONSTRAINTS * 2);
+    let method = Method::new("method_test").constraints(vec![0x00; MAX_CONSTRAINTS + 1]);
+    let modules = vec![Module::new("module_test").methods(vec![method])];
+
+    for trnnut in [
+        TRNNut::V0(TRNNutV0 {
+            modules: modules.clone(),
+        }),
+        TRNNut::V1(TRNNutV1 { modules }),
+    ] {
+        assert_eq!(trnnut.try_encode(), Err(EncodeError::ConstraintsTooLong));
+        let encoded = trnnut.try_encode_limited(limits).unwrap();
+        assert_eq!(
+            TRNNut::decode(&mut &encoded[..]),
+            Err(codec::Error::from("constraints are too long"))
+        );
+        assert_eq!(
+            TRNNut::decode_limited(&mut &encoded[..], limits),
+            Ok(trnnut)
+        );
+    }
+}
+
+#[test]
+fn it_decodes_versioned_trnnut_v0() {
+    let encoded: Vec<u8> = vec![
+        0, 0, 0, 1, 109, 111, 100, 117, 108, 101, 95, 116, 101, 115, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 81, 1, 0, 0, 109, 101, 116, 104, 111, 100, 95,
+        116, 101, 115, 116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let trnnut = TRNNut::decode(&mut &encoded[..]).expect("it works");
+
+    assert_eq!(trnnut.version(), 0);
+    assert_eq!(trnnut.modules().len(), 1);
+    assert_eq!(
+        trnnut
+            .get_module("module_test")
+            .expect("module exists")
+            .block_cooldown,
+        Some(86_400)
+    );
+    assert_eq!(
+        trnnut.validate_module("module_test", "method_test", &[]),
+        Ok(())
+    );
+    assert_eq!(trnnut.encode(), encoded);
+    assert_eq!(
+        TRNNutV0::try_from(trnnut),
+        TRNNutV0::decode(&mut &encoded[..])
+    );
+}
+
+#[test]
+fn it_decodes_versioned_trnnut_v1() {
+    let method = Method::new("method_test");
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test").methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut_v1 = TRNNutV1 { modules };
+    let encoded = trnnut_v1.encode();
+    let trnnut = TRNNut::decode(&mut &encoded[..]).expect("it works");
+
+    assert_eq!(trnnut, TRNNut::V1(trnnut_v1));
+    assert_eq!(trnnut.version(), 1);
+    assert_eq!(
+        trnnut.validate_module("module_test", "method_test2", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+    assert_eq!(trnnut.encode(), encoded);
+    assert_eq!(
+        TRNNutV0::try_from(trnnut),
+        Err(codec::Error::from("expected version : 0"))
+    );
+}
+
+#[test]
+fn it_fails_decode_versioned_trnnut_with_unknown_version() {
+    let encoded: Vec<u8> = vec![2, 0, 0];
+    assert_eq!(
+        TRNNut::decode(&mut &encoded[..]),
+        Err(codec::Error::from("unsupported trnnut version"))
+    );
+}
+
+#[test]
+fn it_decodes_strict() {
+    let encoded: Vec<u8> = vec![
+        0, 0, 0, 0, 109, 111, 100, 117, 108, 101, 95, 116, 101, 115, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 109, 101, 116, 104, 111, 100, 95, 116, 101, 115,
+        116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 34, 0, 192, 128, 16,
+        246, 0, 0, 0, 0, 0, 0, 0, 128, 16, 178, 128, 0, 0, 0, 0, 0, 0, 0, 224, 116, 101, 115, 116,
+        105, 110, 103, 0, 0, 0, 17,
+    ];
+
+    assert_eq!(
+        TRNNutV0::decode_strict(&encoded),
+        TRNNutV0::decode(&mut &encoded[..])
+    );
+    assert_eq!(
+        TRNNut::decode_strict(&encoded),
+        TRNNut::decode(&mut &encoded[..])
+    );
+}
+
+#[test]
+fn it_fails_decode_strict_with_trailing_bytes() {
+    // same as `it_works_get_pact` which has a trailing 0 byte
+    let encoded: Vec<u8> = vec![
+        0, 0, 0, 0, 109, 111, 100, 117, 108, 101, 95, 116, 101, 115, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 109, 101, 116, 104, 111, 100, 95, 116, 101, 115,
+        116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 34, 0, 192, 128, 16,
+        246, 0, 0, 0, 0, 0, 0, 0, 128, 16, 178, 128, 0, 0, 0, 0, 0, 0, 0, 224, 116, 101, 115, 116,
+        105, 110, 103, 0, 0, 0, 17, 0,
+    ];
+
+    assert!(TRNNutV0::decode(&mut &encoded[..]).is_ok());
+    assert_eq!(
+        TRNNutV0::decode_strict(&encoded),
+        Err(codec::Error::from("unexpected trailing bytes"))
+    );
+    assert_eq!(
+        TRNNut::decode_strict(&encoded),
+        Err(codec::Error::from("unexpected trailing bytes"))
+    );
+}
+
+#[test]
+fn it_fails_decode_strict_with_non_zero_name_padding() {
+    // a leading NUL is trimmed by the lenient decoder, so the
+    // decoded module name would not survive a re-encode
+    let encoded: Vec<u8> = [
+        vec![0, 0, 0, 0],
+        vec![0],
+        vec![b'm'; 31],
+        vec![0],
+        vec![b'm'; 32],
+    ]
+    .concat();
+
+    assert!(TRNNutV0::decode(&mut &encoded[..]).is_ok());
+    assert_eq!(
+        TRNNutV0::decode_strict(&encoded),
+        Err(codec::Error::from("module names should be zero padded"))
+    );
+}
+
+#[test]
+fn it_fails_to_ensure_canonical_when_re_encoding_differs() {
+    let method = Method::new("method_test");
+    let encoded = method.encode();
+    let other = Method::new("method_test2").encode();
+
+    assert_eq!(ensure_canonical(method.clone(), &encoded, &[]), Ok(method.clone()));
+    assert_eq!(
+        ensure_canonical(method, &other, &[]),
+        Err(codec::Error::from("non-canonical encoding"))
+    );
+}
+
+#[test]
+fn it_fails_decode_strict_with_undefined_method_flags() {
+    let mut encoded: Vec<u8> = vec![
+        0, 0, 0, 0, 109, 111, 100, 117, 108, 101, 95, 116, 101, 115, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 109, 101, 116, 104, 111, 100, 95, 116, 101, 115,
+        116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    encoded[36] = 0b0100_0000;
+
+    assert!(TRNNutV0::decode(&mut &encoded[..]).is_ok());
+    assert_eq!(
+        TRNNutV0::decode_strict(&encoded),
+        Err(codec::Error::from("undefined method flags"))
+    );
+}
+
+#[test]
+fn it_decodes_strict_v1() {
+    let method = Method::new("method_test").block_cooldown(123);
+    let methods = make_methods(&method);
+
+    let module = Module::new("module_test").methods(methods);
+    let modules = make_modules(&module);
+
+    let trnnut = TRNNutV1 { modules };
+    let encoded = trnnut.encode();
+
+    assert_eq!(TRNNutV1::decode_strict(&encoded), Ok(trnnut.clone()));
+    assert_eq!(TRNNut::decode_strict(&encoded), Ok(TRNNut::V1(trnnut)));
+    assert_eq!(
+        TRNNutV1::decode_strict(&[encoded, vec![0]].concat()),
+        Err(codec::Error::from("unexpected trailing bytes"))
+    );
+}
+
+pub(crate) fn make_trnnut(modules: Vec<Module>) -> TRNNutV0 {
+    TRNNutV0 { modules }
+}
+
+#[test]
+fn it_is_a_subset_of_itself() {
+    let trnnut = make_trnnut(vec![
+        Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer").block_cooldown(5)]),
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+    ]);
+    assert_eq!(trnnut.is_subset_of(&trnnut), Ok(()));
+}
+
+#[test]
+fn it_is_a_subset_of_a_wildcard_parent() {
+    let parent = make_trnnut(vec![
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)])
+    ]);
+    let child = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")]),
+        Module::new("Nft").methods(vec![Method::new(WILDCARD)]),
+    ]);
+
+    assert_eq!(child.is_subset_of(&parent), Ok(()));
+    assert_eq!(
+        parent.is_subset_of(&child),
+        Err(AttenuationViolation::MethodNotGranted {
+            module: "Balances".into(),
+            method: WILDCARD.into(),
+        })
+    );
+}
+
+#[test]
+fn it_is_not_a_subset_when_a_module_is_not_granted() {
+    let parent = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")])
+    ]);
+    let child = make_trnnut(vec![
+        Module::new("Nft").methods(vec![Method::new("mint")])
+    ]);
+
+    assert_eq!(
+        child.is_subset_of(&parent),
+        Err(AttenuationViolation::ModuleNotGranted {
+            module: "Nft".into()
+        })
+    );
+}
+
+#[test]
+fn it_is_not_a_subset_when_a_wildcard_covers_a_restricted_parent_module() {
+    let parent = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")]),
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+    ]);
+    let child = make_trnnut(vec![
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)])
+    ]);
+
+    assert_eq!(
+        child.is_subset_of(&parent),
+        Err(AttenuationViolation::MethodNotGranted {
+            module: "Balances".into(),
+            method: WILDCARD.into(),
+        })
+    );
+}
+
+#[test]
+fn it_is_not_a_subset_when_a_method_is_not_granted() {
+    let parent = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")])
+    ]);
+    let child = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new("transfer"),
+        Method::new("force_transfer"),
+    ])]);
+
+    assert_eq!(
+        child.is_subset_of(&parent),
+        Err(AttenuationViolation::MethodNotGranted {
+            module: "Balances".into(),
+            method: "force_transfer".into(),
+        })
+    );
+}
+
+#[test]
+fn it_requires_cooldowns_at_least_as_long_as_the_parent() {
+    let parent = make_trnnut(vec![Module::new("Balances")
+        .block_cooldown(10)
+        .methods(vec![Method::new("transfer").block_cooldown(20)])]);
+    let no_module_cooldown = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer").block_cooldown(20)])
+    ]);
+    let short_method_cooldown = make_trnnut(vec![Module::new("Balances")
+        .block_cooldown(10)
+        .methods(vec![Method::new("transfer").block_cooldown(19)])]);
+    let longer_cooldowns = make_trnnut(vec![Module::new("Balances")
+        .block_cooldown(11)
+        .methods(vec![Method::new("transfer").block_cooldown(21)])]);
+
+    assert_eq!(
+        no_module_cooldown.is_subset_of(&parent),
+        Err(AttenuationViolation::CooldownTooShort {
+            module: "Balances".into(),
+            method: None,
+        })
+    );
+    assert_eq!(
+        short_method_cooldown.is_subset_of(&parent),
+        Err(AttenuationViolation::CooldownTooShort {
+            module: "Balances".into(),
+            method: Some("transfer".into()),
+        })
+    );
+    assert_eq!(longer_cooldowns.is_subset_of(&parent), Ok(()));
+}
+
+#[test]
+fn it_requires_the_parents_constraints() {
+    let pact = PactContract {
+        data_table: DataTable::new(vec![PactType::Numeric(Numeric(123))]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00].to_vec(),
+    };
+    let mut constraints: Vec<u8> = Vec::new();
+    pact.encode(&mut constraints);
+
+    let unconstrained = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")])
+    ]);
+    let constrained = make_trnnut(vec![Module::new("Balances")
+        .methods(vec![Method::new("transfer").constraints(constraints)])]);
+
+    assert_eq!(constrained.is_subset_of(&unconstrained), Ok(()));
+    assert_eq!(constrained.is_subset_of(&constrained), Ok(()));
+    assert_eq!(
+        unconstrained.is_subset_of(&constrained),
+        Err(AttenuationViolation::ConstraintsNotAttenuated {
+            module: "Balances".into(),
+            method: "transfer".into(),
+        })
+    );
+}
+
+#[test]
+fn it_intersects_exact_modules() {
+    let a = make_trnnut(vec![Module::new("Balances").block_cooldown(10).methods(vec![
+        Method::new("transfer").block_cooldown(1),
+        Method::new("burn"),
+    ])]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer").block_cooldown(5)]),
+        Module::new("Nft").methods(vec![Method::new("mint")]),
+    ]);
+
+    assert_eq!(
+        a.intersect(&b),
+        Ok(make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer").block_cooldown(5)])]))
+    );
+}
+
+#[test]
+fn it_intersects_with_wildcards() {
+    let org_policy = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")]),
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+    ]);
+    let user_grant = make_trnnut(vec![Module::new(WILDCARD)
+        .block_cooldown(3)
+        .methods(vec![Method::new(WILDCARD)])]);
+
+    let intersection = org_policy.intersect(&user_grant).unwrap();
+
+    assert_eq!(
+        intersection,
+        make_trnnut(vec![
+            Module::new("Balances")
+                .block_cooldown(3)
+                .methods(vec![Method::new("transfer")]),
+            Module::new(WILDCARD)
+                .block_cooldown(3)
+                .methods(vec![Method::new(WILDCARD)]),
+        ])
+    );
+    assert_eq!(intersection.is_subset_of(&org_policy), Ok(()));
+    assert_eq!(intersection.is_subset_of(&user_grant), Ok(()));
+}
+
+#[test]
+fn it_unions_with_wildcards() {
+    let a = make_trnnut(vec![Module::new("Balances")
+        .block_cooldown(10)
+        .methods(vec![Method::new("transfer").block_cooldown(5)])]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").block_cooldown(20).methods(vec![
+            Method::new("transfer").block_cooldown(2),
+            Method::new("burn"),
+        ]),
+        Module::new(WILDCARD).methods(vec![Method::new("registered_method")]),
+    ]);
+
+    let union = a.union(&b).unwrap();
+
+    assert_eq!(
+        union,
+        make_trnnut(vec![
+            Module::new("Balances").block_cooldown(10).methods(vec![
+                Method::new("transfer").block_cooldown(2),
+                Method::new("burn"),
+            ]),
+            Module::new(WILDCARD).methods(vec![Method::new("registered_method")]),
+        ])
+    );
+    assert_eq!(a.is_subset_of(&union), Ok(()));
+    assert_eq!(b.is_subset_of(&union), Ok(()));
+}
+
+#[test]
+fn it_combines_constraints() {
+    let pact = PactContract {
+        data_table: DataTable::new(vec![PactType::Numeric(Numeric(123))]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00].to_vec(),
+    };
+    let mut constraints: Vec<u8> = Vec::new();
+    pact.encode(&mut constraints);
+    // XOR has no `Constraint` equivalent, so the contract can't be combined
+    let xor = PactContract {
+        data_table: DataTable::new(vec![PactType::Numeric(Numeric(123))]),
+        bytecode: [
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x00,
+            OpCode::CONJ(Conjunction::new(OpConj::XOR)).into(),
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x10,
+        ]
+        .to_vec(),
+    };
+    let mut xor_constraints: Vec<u8> = Vec::new();
+    xor.encode(&mut xor_constraints);
+
+    let constrained = make_trnnut(vec![Module::new("Balances")
+        .methods(vec![Method::new("transfer").constraints(constraints.clone())])]);
+    let unconstrained = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")])
+    ]);
+    let xor_constrained = make_trnnut(vec![Module::new("Balances")
+        .methods(vec![Method::new("transfer").constraints(xor_constraints)])]);
+
+    assert_eq!(constrained.intersect(&unconstrained), Ok(constrained.clone()));
+    assert_eq!(unconstrained.union(&constrained), Ok(unconstrained.clone()));
+    for combined in [
+        constrained.intersect(&xor_constrained),
+        constrained.union(&xor_constrained),
+    ] {
+        assert_eq!(
+            combined,
+            Err(CombineError::ConflictingConstraints {
+                module: "Balances".into(),
+                method: "transfer".into(),
+            })
+        );
+    }
+}
+
+#[test]
+fn it_combines_differing_constraints() {
+    let constrain = |constraint: Constraint| {
+        make_trnnut(vec![Module::new("Balances").methods(vec![Method::new(
+            "transfer",
+        )
+        .block_cooldown(5)
+        .constrain(constraint)
+        .unwrap()])])
+    };
+    let at_most_100 = constrain(arg(0).le(100));
+    let exactly_5 = constrain(arg(0).eq(5));
+    let transfer = |amount: u64| {
+        move |trnnut: &TRNNutV0| {
+            trnnut.validate_module(
+                "Balances",
+                "transfer",
+                &[PactType::Numeric(Numeric(amount))],
+            )
+        }
+    };
+
+    let intersection = at_most_100.intersect(&exactly_5).unwrap();
+    assert_eq!(intersection, constrain(arg(0).le(100).and(arg(0).eq(5))));
+    assert_eq!(transfer(5)(&intersection), Ok(()));
+    assert_eq!(
+        transfer(6)(&intersection),
+        Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
+    );
+
+    // Neither constraint is dropped, as that would grant more than either side
+    let union = at_most_100.union(&exactly_5).unwrap();
+    assert_eq!(union, constrain(arg(0).le(100).or(arg(0).eq(5))));
+    assert_eq!(transfer(100)(&union), Ok(()));
+    assert_eq!(
+        transfer(101)(&union),
+        Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
+    );
+}
+
+#[test]
+fn it_fails_to_intersect_when_a_wildcard_would_grant_an_emptied_module() {
+    let a = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer")]),
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+    ]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("burn")]),
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+    ]);
+
+    assert_eq!(
+        a.intersect(&b),
+        Err(CombineError::NoMethods {
+            module: "Balances".into()
+        })
+    );
+}
+
+#[test]
+fn it_fails_to_union_too_many_modules() {
+    let method = Method::new("registered_method");
+    let methods = make_methods(&method);
+    let mut a_modules: Vec<Module> = Vec::default();
+    let mut b_modules: Vec<Module> = Vec::default();
+    for x in 0..MAX_MODULES {
+        a_modules.push(Module::new(&x.to_string()).methods(methods.clone()));
+        b_modules.push(Module::new(&(x + MAX_MODULES).to_string()).methods(methods.clone()));
+    }
+
+    assert_eq!(
+        make_trnnut(a_modules).union(&make_trnnut(b_modules)),
+        Err(CombineError::TooManyModules)
+    );
+}
+
+#[test]
+fn it_prefers_exact_names_then_longest_patterns_then_wildcards() {
+    let module = Module::new("Balances").methods(vec![
+        Method::new(WILDCARD).block_cooldown(1),
+        Method::new("transfer_*").block_cooldown(2),
+        Method::new("transfer_keep_*").block_cooldown(3),
+        Method::new("transfer_all").block_cooldown(4),
+    ]);
+    let name = |method| module.get_method(method).map(|m| m.name.as_str());
+
+    assert_eq!(name("transfer_all"), Some("transfer_all"));
+    assert_eq!(name("transfer_keep_alive"), Some("transfer_keep_*"));
+    assert_eq!(name("transfer_from"), Some("transfer_*"));
+    assert_eq!(name("transfer"), Some(WILDCARD));
+}
+
+#[test]
+fn it_validates_suffix_and_namespace_patterns() {
+    let trnnut = make_trnnut(vec![
+        Module::new("nft::*").methods(vec![Method::new("*_keep_alive")])
+    ]);
+
+    assert_eq!(
+        trnnut.validate_module("nft::Marketplace", "transfer_keep_alive", &[]),
+        Ok(())
+    );
+    assert_eq!(
+        trnnut.validate_module("nft", "transfer_keep_alive", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Module))
+    );
+    assert_eq!(
+        trnnut.validate_module("nft::Marketplace", "transfer", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+}
+
+#[test]
+fn it_is_a_subset_only_of_broader_patterns() {
+    let parent = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer_*")])
+    ]);
+    let narrower = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer_all")])
+    ]);
+    let broader = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer*")])
+    ]);
+
+    assert_eq!(narrower.is_subset_of(&parent), Ok(()));
+    assert_eq!(
+        broader.is_subset_of(&parent),
+        Err(AttenuationViolation::MethodNotGranted {
+            module: "Balances".into(),
+            method: "transfer*".into(),
+        })
+    );
+}
+
+#[test]
+fn it_intersects_overlapping_patterns() {
+    let a = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new("transfer_*").block_cooldown(5),
+        Method::new(WILDCARD),
+    ])]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("*_keep_alive")])
+    ]);
+
+    let intersection = a.intersect(&b).unwrap();
+
+    assert_eq!(
+        intersection,
+        make_trnnut(vec![Module::new("Balances").methods(vec![
+            Method::new("transfer_keep_alive").block_cooldown(5),
+            Method::new("transfer_*_keep_alive").block_cooldown(5),
+            Method::new("*_keep_alive"),
+        ])])
+    );
+    assert_eq!(intersection.is_subset_of(&a), Ok(()));
+    assert_eq!(intersection.is_subset_of(&b), Ok(()));
+    assert_eq!(
+        intersection.validate_module("Balances", "transfer_all", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+}
+
+#[test]
+fn it_drops_patterns_a_broader_entry_grants_identically() {
+    let a = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("transfer_*")])
+    ]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new(WILDCARD)])
+    ]);
+
+    assert_eq!(a.intersect(&b), Ok(a.clone()));
+    assert_eq!(a.union(&b), Ok(b.clone()));
+}
+
+#[test]
+fn it_denies_methods_before_granting_them() {
+    let trnnut = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new(WILDCARD),
+        Method::new("force_*").deny(),
+        Method::new("force_transfer"),
+    ])]);
+
+    assert_eq!(trnnut.validate_module("Balances", "transfer", &[]), Ok(()));
+    assert_eq!(
+        trnnut.validate_module("Balances", "force_transfer", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+    assert_eq!(
+        trnnut.validate_module("Balances", "force_unreserve", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+    );
+}
+
+#[test]
+fn it_encodes_and_decodes_denied_methods() {
+    let modules = vec![Module::new("Balances").methods(vec![
+        Method::new(WILDCARD),
+        Method::new("force_transfer").deny(),
+    ])];
+    let trnnut = TRNNutV1 {
+        modules: modules.clone(),
+    };
+
+    let encoded = trnnut.try_encode().unwrap();
+
+    assert_eq!(TRNNutV1::decode_strict(&encoded), Ok(trnnut));
+    // Version 0 decoders would ignore the deny flag and grant the method
+    assert_eq!(
+        make_trnnut(modules).try_encode(),
+        Err(EncodeError::MethodDenyUnsupported {
+            method: "force_transfer".into()
+        })
+    );
+}
+
+#[test]
+fn it_denies_modules_before_granting_them() {
+    let modules = vec![
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)]),
+        Module::new("Sudo").deny(),
+    ];
+    let trnnut = TRNNutV1 {
+        modules: modules.clone(),
+    };
+
+    assert_eq!(trnnut.validate_module("Balances", "transfer", &[]), Ok(()));
+    assert_eq!(
+        trnnut.validate_module("Sudo", "sudo", &[]),
+        Err(ValidationErr::NoPermission(RuntimeDomain::Module))
+    );
+    assert_eq!(
+        TRNNutV1::decode_strict(&trnnut.try_encode().unwrap()),
+        Ok(trnnut)
+    );
+    assert_eq!(
+        make_trnnut(modules).try_encode(),
+        Err(EncodeError::ModuleDenyUnsupported {
+            module: "Sudo".into()
+        })
+    );
+}
+
+#[test]
+fn it_is_not_a_subset_when_granting_what_the_parent_denies() {
+    let parent = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new(WILDCARD),
+        Method::new("force_transfer").deny(),
+    ])]);
+    let child = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new(WILDCARD)])
+    ]);
+
+    assert_eq!(parent.is_subset_of(&child), Ok(()));
+    assert_eq!(
+        child.is_subset_of(&parent),
+        Err(AttenuationViolation::MethodNotGranted {
+            module: "Balances".into(),
+            method: "force_transfer".into(),
+        })
+    );
+}
+
+#[test]
+fn it_keeps_deny_entries_when_intersecting() {
+    let a = make_trnnut(vec![
+        Module::new(WILDCARD).methods(vec![
+            Method::new(WILDCARD),
+            Method::new("force_transfer").deny(),
+        ]),
+        Module::new("Sudo").deny(),
+    ]);
+    let b = make_trnnut(vec![
+        Module::new(WILDCARD).methods(vec![Method::new(WILDCARD)])
+    ]);
+
+    assert_eq!(b.intersect(&a), Ok(a.clone()));
+}
+
+#[test]
+fn it_fails_to_union_a_deny_covering_a_grant() {
+    let a = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new(WILDCARD),
+        Method::new("t*").deny(),
+    ])]);
+    let b = make_trnnut(vec![
+        Module::new("Balances").methods(vec![Method::new("tr*")])
+    ]);
+
+    assert_eq!(
+        a.union(&b),
+        Err(CombineError::ConflictingDeny {
+            module: "Balances".into(),
+            method: Some("tr*".into()),
+        })
+    );
+}
+
+#[test]
+fn it_explains_a_failed_constraint() {
+    let pact = PactContract {
+        data_table: DataTable::new(vec![
+            PactType::Numeric(Numeric(123)),
+            PactType::StringLike(StringLike(b"AUD".to_vec())),
+        ]),
+        bytecode: [
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x00,
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x11,
+        ]
+        .to_vec(),
+    };
+    let mut constraints: Vec<u8> = Vec::new();
+    pact.encode(&mut constraints);
+
+    let trnnut = make_trnnut(vec![Module::new("Balances").methods(vec![
+        Method::new(WILDCARD).constraints(constraints)
+    ])]);
+    let args = [
+        PactType::Numeric(Numeric(123)),
+        PactType::StringLike(StringLike(b"USD".to_vec())),
+    ];
+
+    let trace = trnnut.validate_module_explain("Balances", "transfer", &args);
+
+    assert_eq!(
+        trace.module,
+        Some(MatchedEntry {
+            name: "Balances".into(),
+            kind: MatchKind::Exact,
+            deny: false,
+        })
+    );
+    assert_eq!(
+        trace.method,
+        Some(MatchedEntry {
+            name: WILDCARD.into(),
+            kind: MatchKind::Wildcard,
+            deny: false,
+        })
+    );
+    assert_eq!(
+        trace.pact,
+        PactTrace::Failed(Some(ConstraintsFailure {
+            opcode_index: 2,
+            opcode: OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            arg_index: 1,
+            rhs_index: 1,
+            rhs_is_arg: false,
+        }))
+    );
+    assert_eq!(
+        trace.result,
+        trnnut.validate_module("Balances", "transfer", &args)
+    );
+    assert_eq!(
+        trace.to_string(),
+        "module Balances: granted by Balances (exact); \
+        method transfer: granted by * (wildcard); \
+        constraints failed at opcode 0x00 at 2, comparing argument 1 with data table item 1; \
+        TRNNut does not grant permission for method arguments"
+    );
+}
+
+#[test]
+fn it_explains_the_comparison_deciding_an_or_chain() {
+    let method = Method::new("transfer")
+        .constrain(arg(0).eq(1).or(arg(1).eq(2)).and(arg(2).eq(3)))
+        .unwrap();
+    let trnnut = make_trnnut(vec![Module::new("Balances").methods(vec![method])]);
+    // `arg1 == 2` fails, but `arg0 == 1` holds, so only `arg2 == 3` decides the result
+    let args = [
+        PactType::Numeric(Numeric(1)),
+        PactType::Numeric(Numeric(0)),
+        PactType::Numeric(Numeric(0)),
+    ];
+
+    let trace = trnnut.validate_module_explain("Balances", "transfer", &args);
+
+    assert_eq!(
+        trace.pact,
+        PactTrace::Failed(Some(ConstraintsFailure {
+            opcode_index: 5,
+            opcode: OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            arg_index: 2,
+            rhs_index: 2,
+            rhs_is_arg: false,
+        }))
+    );
+    assert_eq!(
+        trace.result,
+        trnnut.validate_module("Balances", "transfer", &args)
+    );
+
+    // When no term of `Any` holds, its last term decides
+    let args = [
+        PactType::Numeric(Numeric(0)),
+        PactType::Numeric(Numeric(0)),
+        PactType::Numeric(Numeric(3)),
+    ];
+    let trace = trnnut.validate_module_explain("Balances", "transfer", &args);
+    assert!(
+        matches!(trace.pact, PactTrace::Failed(Some(failure)) if failure.arg_index == 1),
+        "{}",
+        trace.pact
+    );
+}
+
+#[test]
+fn it_explains_the_same_result_as_validate_module() {
+    let trnnut = make_trnnut(vec![
+        Module::new("Balances").methods(vec![
+            Method::new("transfer_*"),
+            Method::new("transfer_all").deny(),
+        ]),
+        Module::new("nft::*").methods(vec![Method::new("mint")]),
+    ]);
+    let cases = [
+        ("Balances", "transfer_keep_alive", Some(MatchKind::Pattern)),
+        ("Balances", "transfer_all", Some(MatchKind::Exact)),
+        ("Balances", "burn", None),
+        ("nft::Marketplace", "mint", Some(MatchKind::Exact)),
+        ("Sudo", "sudo", None),
+    ];
+
+    for (module, method, kind) in cases {
+        let trace = trnnut.validate_module_explain(module, method, &[]);
+        assert_eq!(trace.result, trnnut.validate_module(module, method, &[]));
+        assert_eq!(trace.pact, PactTrace::NotRun);
+        assert_eq!(trace.method.map(|m| m.kind), kind);
+    }
+
+    let denied = trnnut.validate_module_explain("Balances", "transfer_all", &[]);
+    assert_eq!(
+        denied.method,
+        Some(MatchedEntry {
+            name: "transfer_all".into(),
+            kind: MatchKind::Exact,
+            deny: true,
+        })
+    );
+    assert_eq!(
+        trnnut
+            .validate_module_explain("nft::Marketplace", "mint", &[])
+            .module
+            .map(|m| m.kind),
+        Some(MatchKind::Pattern)
+    );
+}
+
+#[test]
+fn it_displays() {
+    let trnnut = make_trnnut(vec![
+        Module::new("Balances").block_cooldown(10).methods(vec![
+            Method::new("transfer")
+                .constrain(arg(0).le(1000))
+                .unwrap(),
+            Method::new("transfer_*").block_cooldown(5),
+        ]),
+        Module::new("*").methods(vec![Method::new("*")]),
+    ]);
+    let size = trnnut.encode().len();
+
+    assert_eq!(
+        trnnut.to_string(),
+        format!(
+            "TRNNut v0, {size} bytes: module Balances cooldown 10 {{ method transfer where \
+             arg0 <= 1000; method transfer_* cooldown 5 }}, module * {{ method * }}"
+        )
+    );
+    assert_eq!(
+        format!("{trnnut:#}"),
+        format!(
+            "TRNNut v0, {size} bytes, 2 modules
+  module Balances cooldown 10
+    method transfer where arg0 <= 1000
+    method transfer_* (pattern) cooldown 5
+  module * (wildcard)
+    method * (wildcard)"
+        )
+    );
+    assert_eq!(format!("{:#}", TRNNut::V0(trnnut.clone())), format!("{trnnut:#}"));
+
+    let denied = make_trnnut(vec![Module::new("Sudo").deny()]);
+    assert!(denied.to_string().starts_with("TRNNut v0, not encodable as "));
+}
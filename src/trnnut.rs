@@ -7,12 +7,28 @@
 
 
 
-use alloc::vec::Vec;
+use alloc::{
+    fmt::{self, Display, Formatter},
+    vec::Vec,
+};
 use codec::{Decode, Encode, Input, Output};
 use core::convert::TryFrom;
 use pact::{interpreter::interpret, types::PactType};
 
-use crate::{module, PartialDecode, RuntimeDomain, ValidationErr, WILDCARD};
+use crate::{
+    attenuation::{modules_are_subset_of, AttenuationViolation},
+    combine::{combine_modules, Combine, CombineError},
+    cooldown::{remaining_blocks, CooldownStore},
+    edit::{self, edit_modules, EditError},
+    encode::{EncodeError, TryEncode},
+    ensure_canonical,
+    explain::{explain_modules, ValidationTrace},
+    limits::Limits,
+    method::Method,
+    module,
+    pattern::{most_specific, specificity},
+    LimitedDecode, PartialDecode, RuntimeDomain, StrictDecode, ValidationErr,
+};
 use module::Module;
 
 pub const MAX_MODULES: usize = 256;
@@ -28,61 +44,264 @@ pub struct TRNNutV0 {
 
 impl TRNNutV0 {
     /// Returns the module, if it exists in the TRNNut
-    /// Wildcard modules have lower priority than defined modules
+    /// Exact names have priority over patterns, then longer pattern prefixes, then suffixes
     pub fn get_module(&self, module: &str) -> Option<&Module> {
-        let mut outcome: Option<&Module> = None;
-        for m in &self.modules {
-            if m.name == module {
-                outcome = Some(m);
-                break;
-            } else if m.name == WILDCARD {
-                outcome = Some(m);
-            }
+        find_module(&self.modules, module)
+    }
+
+    /// Checks this TRNNut grants no more than `other`, i.e. it is a valid re-delegation
+    /// Patterns are resolved with the same priority rules as `get_module`
+    ///
+    /// # Errors
+    ///
+    /// Returns the first permission granted here but not by `other`
+    pub fn is_subset_of(&self, other: &Self) -> Result<(), AttenuationViolation> {
+        modules_are_subset_of(&self.modules, &other.modules)
+    }
+
+    /// Returns a TRNNut granting only what both this TRNNut and `other` grant
+    /// Cooldowns take the longer of the two and differing constraints must both hold
+    ///
+    /// # Errors
+    ///
+    /// Will return error if the result can't be represented within `MAX_MODULES`/`MAX_METHODS`,
+    /// or differing constraints don't combine into one Pact contract
+    pub fn intersect(&self, other: &Self) -> Result<Self, CombineError> {
+        Ok(Self {
+            modules: combine_modules(Combine::Intersect, &self.modules, &other.modules)?,
+        })
+    }
+
+    /// Returns a TRNNut granting anything this TRNNut or `other` grants
+    /// Cooldowns take the shorter of the two and either of differing constraints must hold
+    ///
+    /// # Errors
+    ///
+    /// Will return error if the result can't be represented within `MAX_MODULES`/`MAX_METHODS`,
+    /// or differing constraints don't combine into one Pact contract
+    pub fn union(&self, other: &Self) -> Result<Self, CombineError> {
+        Ok(Self {
+            modules: combine_modules(Combine::Union, &self.modules, &other.modules)?,
+        })
+    }
+
+    /// Adds `module`, unless a module of the same name exists
+    ///
+    /// # Errors
+    ///
+    /// Will return error if `module` is denied, has no, duplicate or invalid entries, or would break an encoding limit
+    pub fn add_module(&mut self, module: Module) -> Result<(), EditError> {
+        edit_modules(&mut self.modules, |modules| {
+            edit::add_module(modules, module)
+        })
+    }
+
+    /// Removes and returns the module named `module`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if `module` does not exist, or is the last module
+    pub fn remove_module(&mut self, module: &str) -> Result<Module, EditError> {
+        edit::remove_module(&mut self.modules, module)
+    }
+
+    /// Adds `method` to `module`, returning the method of the same name it replaces
+    ///
+    /// # Errors
+    ///
+    /// Will return error if `module` does not exist, or `method` is invalid or would break an encoding limit
+    pub fn upsert_method(
+        &mut self,
+        module: &str,
+        method: Method,
+    ) -> Result<Option<Method>, EditError> {
+        edit_modules(&mut self.modules, |modules| {
+            edit::upsert_method(modules, module, method)
+        })
+    }
+
+    /// Removes and returns the method named `method` of `module`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if the method does not exist, or is the last method of `module`
+    pub fn remove_method(&mut self, module: &str, method: &str) -> Result<Method, EditError> {
+        edit::remove_method(&mut self.modules, module, method)
+    }
+
+    /// Sets the cooldown of `method` of `module`, or of `module` itself when `method` is `None`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if the module or method does not exist, or the TRNNut would be too large
+    pub fn set_cooldown(
+        &mut self,
+        module: &str,
+        method: Option<&str>,
+        block_cooldown: Option<u32>,
+    ) -> Result<(), EditError> {
+        edit_modules(&mut self.modules, |modules| {
+            edit::set_cooldown(modules, module, method, block_cooldown)
+        })
+    }
+
+    /// Sets the constraints of `method` of `module`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if the method does not exist, or the constraints are invalid or too long
+    pub fn set_constraints(
+        &mut self,
+        module: &str,
+        method: &str,
+        constraints: Option<Vec<u8>>,
+    ) -> Result<(), EditError> {
+        edit_modules(&mut self.modules, |modules| {
+            edit::set_constraints(modules, module, method, constraints)
+        })
+    }
+}
+
+/// Returns the module, if it exists in `modules`
+/// Exact names have priority over patterns, then longer pattern prefixes, then suffixes
+/// Deny entries are not returned, see `denies_module`
+pub(crate) fn find_module<'a>(modules: &'a [Module], module: &str) -> Option<&'a Module> {
+    most_specific(modules.iter().filter(|m| !m.deny), module, |m| {
+        m.name.as_str()
+    })
+}
+
+/// Returns whether any deny entry of `modules` matches `module`
+/// Deny entries are checked before, and override, every granting entry
+pub(crate) fn denies_module(modules: &[Module], module: &str) -> bool {
+    find_deny(modules, module).is_some()
+}
+
+/// Returns the first deny entry of `modules` matching `module`
+pub(crate) fn find_deny<'a>(modules: &'a [Module], module: &str) -> Option<&'a Module> {
+    modules
+        .iter()
+        .find(|m| m.deny && specificity(&m.name, module).is_some())
+}
+
+/// Returns the module granted by `modules`, unless it is denied
+pub(crate) fn granted_module<'a>(modules: &'a [Module], module: &str) -> Option<&'a Module> {
+    if denies_module(modules, module) {
+        None
+    } else {
+        find_module(modules, module)
+    }
+}
+
+/// Writes a TRNNut of any version, see `Display for TRNNutV0`
+pub(crate) fn fmt_trnnut(
+    f: &mut Formatter<'_>,
+    version: u16,
+    modules: &[Module],
+    encoded: Result<Vec<u8>, EncodeError>,
+) -> fmt::Result {
+    write!(f, "TRNNut v{version}")?;
+    match encoded {
+        Ok(encoded) => write!(f, ", {} bytes", encoded.len())?,
+        Err(err) => write!(f, ", not encodable as {err}")?,
+    }
+    if !f.alternate() {
+        write!(f, ":")?;
+        for (i, module) in modules.iter().enumerate() {
+            let separator = if i > 0 { ", " } else { " " };
+            write!(f, "{separator}{module}")?;
         }
-        outcome
+        return Ok(());
+    }
+    match modules.len() {
+        1 => write!(f, ", 1 module")?,
+        count => write!(f, ", {count} modules")?,
+    }
+    for module in modules {
+        writeln!(f)?;
+        module.fmt_lines(f, 2)?;
     }
+    Ok(())
 }
 
-impl Encode for TRNNutV0 {
-    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
-        if self.modules.is_empty() || self.modules.len() > MAX_MODULES {
-            return;
+/// Renders on one line for logs, with the encoded size and every module
+/// The alternate form, `{:#}`, renders each module and method on its own line,
+/// marks pattern names and summarizes constraints, e.g.
+///
+/// ```text
+/// TRNNut v0, 191 bytes, 2 modules
+///   module Balances cooldown 10
+///     method transfer where arg0 <= 1000
+///     method transfer_* (pattern) cooldown 5
+///   module * (wildcard)
+///     method * (wildcard)
+/// ```
+impl Display for TRNNutV0 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_trnnut(f, 0, &self.modules, self.try_encode())
+    }
+}
+
+impl TryEncode for TRNNutV0 {
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError> {
+        if self.modules.is_empty() {
+            return Err(EncodeError::NoModules);
         }
-        let module_count = u8::try_from(self.modules.len() - 1);
+        if self.modules.len() > MAX_MODULES {
+            return Err(EncodeError::TooManyModules);
+        }
+        let module_count =
+            u8::try_from(self.modules.len() - 1).map_err(|_| EncodeError::TooManyModules)?;
 
         // Encode all modules, but make sure each encoding is valid
         // before modifying the output buffer.
         let mut module_payload_buf: Vec<u8> = Vec::<u8>::default();
         for module in &self.modules {
-            let mut module_buf: Vec<u8> = Vec::<u8>::default();
-            module.encode_to(&mut module_buf);
-            if module_buf.is_empty() {
-                return;
-            }
-            module_payload_buf.write(module_buf.as_slice());
+            module.try_encode_limited_to(&mut module_payload_buf, limits)?;
         }
 
         let mut preliminary_buf = Vec::<u8>::default();
 
         preliminary_buf.write(&VERSION_BYTES);
 
-        preliminary_buf.push_byte(module_count.unwrap());
+        preliminary_buf.push_byte(module_count);
         preliminary_buf.write(module_payload_buf.as_slice());
 
         // Avoid writing outside of the allocated domain buffer
-        if preliminary_buf.len() <= MAX_TRNNUT_BYTES {
-            buf.write(preliminary_buf.as_slice());
+        if preliminary_buf.len() > MAX_TRNNUT_BYTES {
+            return Err(EncodeError::PayloadTooLarge {
+                size: preliminary_buf.len(),
+            });
         }
+        buf.write(preliminary_buf.as_slice());
+
+        Ok(())
     }
 }
 
-impl PartialDecode for TRNNutV0 {
-    fn partial_decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+impl Encode for TRNNutV0 {
+    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+        // Failures are reported by `TryEncode`, nothing is written here
+        let _ = self.try_encode_to(buf);
+    }
+}
+
+impl TRNNutV0 {
+    /// Decode a TRNNut after its version within `limits`, see `Module::decode_with`
+    pub(crate) fn partial_decode_with<I: Input>(
+        input: &mut I,
+        strict: bool,
+        limits: Limits,
+    ) -> Result<Self, codec::Error> {
         let module_count = input.read_byte()? + 1;
         let mut modules = Vec::<Module>::default();
 
         for _ in 0..module_count {
-            let m: Module = Decode::decode(input)?;
+            let m = Module::decode_with(input, strict, limits)?;
             modules.push(m);
         }
 
@@ -90,20 +309,45 @@ impl PartialDecode for TRNNutV0 {
     }
 }
 
+impl PartialDecode for TRNNutV0 {
+    fn partial_decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::partial_decode_with(input, false, Limits::default())
+    }
+}
+
 impl Decode for TRNNutV0 {
     fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::decode_limited(input, Limits::default())
+    }
+}
+
+impl LimitedDecode for TRNNutV0 {
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error> {
         let version = u16::from_le_bytes([input.read_byte()?, input.read_byte()?]);
         if version != 0 {
             return Err(codec::Error::from("expected version : 0"));
         }
-        Self::partial_decode(input)
+        Self::partial_decode_with(input, false, limits)
+    }
+}
+
+impl StrictDecode for TRNNutV0 {
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error> {
+        let mut remaining = input;
+        let version = u16::from_le_bytes([remaining.read_byte()?, remaining.read_byte()?]);
+        if version != 0 {
+            return Err(codec::Error::from("expected version : 0"));
+        }
+        let trnnut = Self::partial_decode_with(&mut remaining, true, Limits::default())?;
+        ensure_canonical(trnnut, input, remaining)
     }
 }
 
 impl TRNNutV0 {
     /// Validates a TRNNut runtime module by:
-    /// (1) looking for `module_name` and `method_name`
-    /// (2) executing the Pact interpreter if constraints exist
+    /// (1) checking no deny entry matches `module_name` or `method_name`
+    /// (2) looking for `module_name` and `method_name`
+    /// (3) executing the Pact interpreter if constraints exist
     ///
     /// # Errors
     ///
@@ -114,21 +358,115 @@ impl TRNNutV0 {
         method_name: &str,
         args: &[PactType],
     ) -> Result<(), ValidationErr<RuntimeDomain>> {
-        let module = self
-            .get_module(module_name)
-            .ok_or_else(|| ValidationErr::NoPermission(RuntimeDomain::Module))?;
-        let method = module
-            .get_method(method_name)
-            .ok_or_else(|| ValidationErr::NoPermission(RuntimeDomain::Method))?;
-        if let Some(pact) = method.get_pact() {
-            match interpret(args, pact.data_table.as_ref(), &pact.bytecode) {
-                Ok(true) => {}
-                Ok(false) => {
-                    return Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
-                }
-                Err(_) => return Err(ValidationErr::ConstraintsInterpretation),
-            }
+        validate_modules(&self.modules, module_name, method_name, args)
+    }
+
+    /// Validates a TRNNut runtime module as `validate_module` does, returning
+    /// a trace of the matching entries and constraints alongside the result
+    pub fn validate_module_explain(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> ValidationTrace {
+        explain_modules(&self.modules, module_name, method_name, args)
+    }
+
+    /// Validates a runtime module as `validate_module` does, then checks
+    /// the module and method block cooldowns of `holder` at `block_number`
+    ///
+    /// Uses are not recorded here, callers should `record_use` on `store`
+    /// once the call has been dispatched.
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails or a cooldown has not yet elapsed
+    pub fn validate_module_at<S: CooldownStore>(
+        &self,
+        block_number: u32,
+        store: &S,
+        holder: &[u8],
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        validate_modules_at(
+            &self.modules,
+            block_number,
+            store,
+            holder,
+            module_name,
+            method_name,
+            args,
+        )
+    }
+}
+
+/// Validates `module_name` and `method_name` against `modules`,
+/// executing the Pact interpreter if constraints exist
+///
+/// Deny entries are checked before the granting entries of each domain
+pub(crate) fn validate_modules(
+    modules: &[Module],
+    module_name: &str,
+    method_name: &str,
+    args: &[PactType],
+) -> Result<(), ValidationErr<RuntimeDomain>> {
+    let module = granted_module(modules, module_name)
+        .ok_or(ValidationErr::NoPermission(RuntimeDomain::Module))?;
+    let method = module
+        .granted_method(method_name)
+        .ok_or(ValidationErr::NoPermission(RuntimeDomain::Method))?;
+    validate_constraints(method, args)
+}
+
+/// Executes the Pact interpreter on `args` if `method` has constraints
+pub(crate) fn validate_constraints(
+    method: &Method,
+    args: &[PactType],
+) -> Result<(), ValidationErr<RuntimeDomain>> {
+    if let Some(pact) = method.get_pact() {
+        match interpret(args, pact.data_table.as_ref(), &pact.bytecode) {
+            Ok(true) => {}
+            Ok(false) => return Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments)),
+            Err(_) => return Err(ValidationErr::ConstraintsInterpretation),
         }
-        Ok(())
     }
+    Ok(())
+}
+
+/// Validates as `validate_modules` does, then checks the module and method
+/// block cooldowns of `holder` at `block_number`
+pub(crate) fn validate_modules_at<S: CooldownStore>(
+    modules: &[Module],
+    block_number: u32,
+    store: &S,
+    holder: &[u8],
+    module_name: &str,
+    method_name: &str,
+    args: &[PactType],
+) -> Result<(), ValidationErr<RuntimeDomain>> {
+    validate_modules(modules, module_name, method_name, args)?;
+
+    let mut remaining = 0;
+    if let Some(module) = find_module(modules, module_name) {
+        remaining = remaining_blocks(
+            module.block_cooldown,
+            store.last_module_use(holder, module_name),
+            block_number,
+        );
+        if let Some(method) = module.get_method(method_name) {
+            remaining = remaining.max(remaining_blocks(
+                method.block_cooldown,
+                store.last_method_use(holder, module_name, method_name),
+                block_number,
+            ));
+        }
+    }
+    if remaining > 0 {
+        return Err(ValidationErr::CooldownActive {
+            remaining_blocks: remaining,
+        });
+    }
+    Ok(())
 }
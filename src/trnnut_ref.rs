@@ -0,0 +1,481 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - Ref
+//!
+//! Borrowed views of encoded TRNNut for use in TRN
+//!
+//! `TRNNutRef` reads modules and methods straight from the bytes of a version 0
+//! TRNNut instead of decoding them into a `TRNNutV0`. Looking up and validating
+//! a call allocates nothing, unless the method granting it has constraints,
+//! whose Pact contract is decoded to be interpreted. Unlike `TRNNutV0::decode`,
+//! creating a view does not decode every Pact contract, so constraints which
+//! are not a Pact contract only fail once a call is validated against them.
+//!
+
+use pact::{
+    interpreter::interpret,
+    types::{Contract as PactContract, PactType},
+};
+
+use crate::{
+    limits::Limits,
+    method::{BLOCK_COOLDOWN_MASK, CONSTRAINTS_MASK, DENY_MASK, LONG_CONSTRAINTS_MASK},
+    module::BLOCK_COOLDOWN_MASK as MODULE_BLOCK_COOLDOWN_MASK,
+    pattern::most_specific_of,
+    trnnut::VERSION_BYTES,
+    RuntimeDomain, ValidationErr,
+};
+
+/// Splits `len` bytes off the front of `input`
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], codec::Error> {
+    if input.len() < len {
+        return Err(codec::Error::from("Not enough data to fill buffer"));
+    }
+    let (taken, remaining) = input.split_at(len);
+    *input = remaining;
+    Ok(taken)
+}
+
+fn take_byte(input: &mut &[u8]) -> Result<u8, codec::Error> {
+    Ok(take(input, 1)?[0])
+}
+
+fn take_u32(input: &mut &[u8]) -> Result<u32, codec::Error> {
+    let bytes = take(input, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Splits a zero padded 32 byte name off the front of `input`
+fn take_name<'a>(
+    input: &mut &'a [u8],
+    missing: &'static str,
+    not_utf8: &'static str,
+) -> Result<&'a str, codec::Error> {
+    let name_buf = take(input, 32).map_err(|_| codec::Error::from(missing))?;
+    core::str::from_utf8(name_buf)
+        .map(|name| name.trim_matches(char::from(0)))
+        .map_err(|_| codec::Error::from(not_utf8))
+}
+
+/// A method of an encoded TRNNut, see `Method`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MethodRef<'a> {
+    pub name: &'a str,
+    pub block_cooldown: Option<u32>,
+    pub constraints: Option<&'a [u8]>,
+}
+
+impl<'a> MethodRef<'a> {
+    fn decode(input: &mut &'a [u8], limits: Limits) -> Result<Self, codec::Error> {
+        let flags = take_byte(input)?;
+        let name = take_name(
+            input,
+            "expected 32 byte method name",
+            "method names should be utf8 encoded",
+        )?;
+        if flags & DENY_MASK == DENY_MASK {
+            return Err(codec::Error::from(
+                "deny entries are only defined for version 1",
+            ));
+        }
+        let block_cooldown = if flags & BLOCK_COOLDOWN_MASK == BLOCK_COOLDOWN_MASK {
+            Some(take_u32(input)?)
+        } else {
+            None
+        };
+        let constraints = if flags & CONSTRAINTS_MASK == CONSTRAINTS_MASK {
+            let length = if flags & LONG_CONSTRAINTS_MASK == LONG_CONSTRAINTS_MASK {
+                let length = take(input, 2)?;
+                let length = usize::from(u16::from_le_bytes([length[0], length[1]]));
+                if length > limits.max_constraints {
+                    return Err(codec::Error::from("constraints are too long"));
+                }
+                length
+            } else {
+                usize::from(take_byte(input)?) + 1
+            };
+            Some(take(input, length)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name,
+            block_cooldown,
+            constraints,
+        })
+    }
+
+    /// Returns the Pact contract, if the method has constraints which decode
+    pub fn get_pact(&self) -> Option<PactContract> {
+        self.constraints
+            .and_then(|constraints| PactContract::decode(constraints).ok())
+    }
+}
+
+/// A module of an encoded TRNNut, see `Module`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModuleRef<'a> {
+    pub name: &'a str,
+    pub block_cooldown: Option<u32>,
+    method_count: usize,
+    /// The encoded methods
+    methods: &'a [u8],
+    limits: Limits,
+}
+
+impl<'a> ModuleRef<'a> {
+    fn decode(input: &mut &'a [u8], limits: Limits) -> Result<Self, codec::Error> {
+        let block_cooldown_and_method_count = take_byte(input)?;
+        let name = take_name(
+            input,
+            "expected 32 byte module name",
+            "module names should be utf8 encoded",
+        )?;
+        let block_cooldown = if block_cooldown_and_method_count & MODULE_BLOCK_COOLDOWN_MASK
+            == MODULE_BLOCK_COOLDOWN_MASK
+        {
+            Some(take_u32(input)?)
+        } else {
+            None
+        };
+
+        let method_count = usize::from(block_cooldown_and_method_count >> 1) + 1;
+        let methods = *input;
+        for _ in 0..method_count {
+            MethodRef::decode(input, limits)?;
+        }
+
+        Ok(Self {
+            name,
+            block_cooldown,
+            method_count,
+            methods: &methods[..methods.len() - input.len()],
+            limits,
+        })
+    }
+
+    /// Returns the methods of the module in encoded order
+    pub fn methods(&self) -> Methods<'a> {
+        Methods {
+            remaining: self.methods,
+            count: self.method_count,
+            limits: self.limits,
+        }
+    }
+
+    /// Returns the method, as `Module::get_method` does
+    pub fn get_method(&self, method: &str) -> Option<MethodRef<'a>> {
+        most_specific_of(self.methods(), method, |m| m.name)
+    }
+}
+
+/// Iterator over the methods of a `ModuleRef`
+#[derive(Clone, Debug)]
+pub struct Methods<'a> {
+    remaining: &'a [u8],
+    count: usize,
+    limits: Limits,
+}
+
+impl<'a> Iterator for Methods<'a> {
+    type Item = MethodRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        // Every method was decoded once already when the view was created
+        MethodRef::decode(&mut self.remaining, self.limits).ok()
+    }
+}
+
+/// Iterator over the modules of a `TRNNutRef`
+#[derive(Clone, Debug)]
+pub struct Modules<'a> {
+    remaining: &'a [u8],
+    count: usize,
+    limits: Limits,
+}
+
+impl<'a> Iterator for Modules<'a> {
+    type Item = ModuleRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        // Every module was decoded once already when the view was created
+        ModuleRef::decode(&mut self.remaining, self.limits).ok()
+    }
+}
+
+/// A view of an encoded version 0 TRNNut, with the lookups and validation of `TRNNutV0`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TRNNutRef<'a> {
+    module_count: usize,
+    /// The encoded modules
+    modules: &'a [u8],
+    limits: Limits,
+}
+
+impl<'a> TRNNutRef<'a> {
+    /// Checks the layout of an encoded version 0 TRNNut and returns a view of it
+    ///
+    /// As with `TRNNutV0::decode`, bytes after the last module are ignored.
+    /// Constraints are only decoded once a call is validated against them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `codec::Error` if `input` is not a version 0 TRNNut
+    pub fn new(input: &'a [u8]) -> Result<Self, codec::Error> {
+        Self::new_limited(input, Limits::default())
+    }
+
+    /// Checks the layout of an encoded version 0 TRNNut within `limits` and returns a view of it,
+    /// see `new`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `codec::Error` if `input` is not a version 0 TRNNut within `limits`
+    pub fn new_limited(input: &'a [u8], limits: Limits) -> Result<Self, codec::Error> {
+        let mut remaining = input;
+        if take(&mut remaining, 2)? != VERSION_BYTES {
+            return Err(codec::Error::from("expected version : 0"));
+        }
+        let module_count = usize::from(take_byte(&mut remaining)?) + 1;
+        let modules = remaining;
+        for _ in 0..module_count {
+            ModuleRef::decode(&mut remaining, limits)?;
+        }
+
+        Ok(Self {
+            module_count,
+            modules: &modules[..modules.len() - remaining.len()],
+            limits,
+        })
+    }
+
+    /// Returns the modules of the TRNNut in encoded order
+    pub fn modules(&self) -> Modules<'a> {
+        Modules {
+            remaining: self.modules,
+            count: self.module_count,
+            limits: self.limits,
+        }
+    }
+
+    /// Returns the module, as `TRNNutV0::get_module` does
+    pub fn get_module(&self, module: &str) -> Option<ModuleRef<'a>> {
+        most_specific_of(self.modules(), module, |m| m.name)
+    }
+
+    /// Returns the method of the module, as `get_module` then `Module::get_method` do
+    pub fn get_method(&self, module: &str, method: &str) -> Option<MethodRef<'a>> {
+        self.get_module(module)?.get_method(method)
+    }
+
+    /// Validates a runtime module as `TRNNutV0::validate_module` does
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails with the type of error embedded in `RuntimeDomain`.
+    /// Constraints which are not a Pact contract, which `TRNNutV0::decode` would have rejected,
+    /// fail with `ValidationErr::ConstraintsInterpretation`.
+    pub fn validate_module(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        let module = self
+            .get_module(module_name)
+            .ok_or(ValidationErr::NoPermission(RuntimeDomain::Module))?;
+        let method = module
+            .get_method(method_name)
+            .ok_or(ValidationErr::NoPermission(RuntimeDomain::Method))?;
+        if let Some(constraints) = method.constraints {
+            let pact = PactContract::decode(constraints)
+                .map_err(|_| ValidationErr::ConstraintsInterpretation)?;
+            match interpret(args, pact.data_table.as_ref(), &pact.bytecode) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err(ValidationErr::NoPermission(RuntimeDomain::MethodArguments))
+                }
+                Err(_) => return Err(ValidationErr::ConstraintsInterpretation),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TRNNutRef;
+    use crate::{
+        constraint::arg,
+        limits::Limits,
+        method::{Method, DENY_MASK, MAX_CONSTRAINTS},
+        module::Module,
+        tests::make_trnnut,
+        RuntimeDomain, TRNNutV0, TryEncode, ValidationErr,
+    };
+    use codec::{Decode, Encode};
+    use pact::types::{Numeric, PactType, StringLike};
+
+    #[test]
+    fn it_views_modules_and_methods() {
+        let encoded = make_trnnut(vec![
+            Module::new("Balances").block_cooldown(10).methods(vec![
+                Method::new("transfer").constrain(arg(0).le(1000)).unwrap(),
+                Method::new("transfer_*").block_cooldown(5),
+            ]),
+            Module::new("nft::*").methods(vec![Method::new("*")]),
+            Module::new("Sudo").methods(vec![Method::new("sudo").constraints(vec![0x00; 300])]),
+        ])
+        .encode();
+        let trnnut = TRNNutRef::new(&encoded).unwrap();
+
+        let names: Vec<&str> = trnnut.modules().map(|m| m.name).collect();
+        assert_eq!(names, ["Balances", "nft::*", "Sudo"]);
+
+        let balances = trnnut.get_module("Balances").unwrap();
+        assert_eq!(balances.block_cooldown, Some(10));
+        assert_eq!(balances.methods().count(), 2);
+        assert_eq!(
+            trnnut
+                .get_method("Balances", "transfer_keep_alive")
+                .map(|m| (m.name, m.block_cooldown)),
+            Some(("transfer_*", Some(5)))
+        );
+        assert_eq!(
+            trnnut
+                .get_method("Sudo", "sudo")
+                .and_then(|m| m.constraints),
+            Some(&[0x00; 300][..])
+        );
+        assert_eq!(
+            trnnut.get_module("nft::Marketplace").map(|m| m.name),
+            Some("nft::*")
+        );
+        assert!(trnnut.get_module("System").is_none());
+    }
+
+    #[test]
+    fn it_validates_as_the_owned_trnnut() {
+        let owned = make_trnnut(vec![
+            Module::new("Balances").methods(vec![
+                Method::new("transfer")
+                    .constrain(arg(0).le(1000).and(arg(1).eq("AUD")))
+                    .unwrap(),
+                Method::new("transfer_*").block_cooldown(5),
+            ]),
+            Module::new("nft::*").methods(vec![Method::new("*")]),
+        ]);
+        let encoded = owned.encode();
+        let trnnut = TRNNutRef::new(&encoded).unwrap();
+        let numeric = |n| PactType::Numeric(Numeric(n));
+        let string = |s: &str| PactType::StringLike(StringLike(s.as_bytes().to_vec()));
+        let cases = [
+            ("Balances", "transfer", vec![numeric(100), string("AUD")]),
+            ("Balances", "transfer", vec![numeric(1001), string("AUD")]),
+            ("Balances", "transfer", vec![string("AUD")]),
+            ("Balances", "transfer_keep_alive", vec![]),
+            ("Balances", "burn", vec![]),
+            ("nft::Marketplace", "list", vec![]),
+            ("System", "remark", vec![]),
+        ];
+
+        for (module, method, args) in cases {
+            assert_eq!(
+                trnnut.validate_module(module, method, &args),
+                owned.validate_module(module, method, &args),
+                "{module}::{method}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_fails_constraints_which_do_not_decode() {
+        let encoded = make_trnnut(vec![
+            Module::new("Balances").methods(vec![Method::new("transfer")]),
+            Module::new("Sudo").methods(vec![Method::new("sudo").constraints(vec![0x00; 300])]),
+        ])
+        .encode();
+        let invalid = [&encoded[..encoded.len() - 300], &[0xff; 300]].concat();
+
+        assert_eq!(
+            TRNNutV0::decode(&mut &invalid[..]),
+            Err(codec::Error::from("invalid constraints codec"))
+        );
+        // The view only decodes the contract of the method a call is validated against
+        let trnnut = TRNNutRef::new(&invalid).unwrap();
+        assert_eq!(
+            trnnut.validate_module("Sudo", "sudo", &[]),
+            Err(ValidationErr::ConstraintsInterpretation)
+        );
+        assert_eq!(
+            trnnut.validate_module("Balances", "burn", &[]),
+            Err(ValidationErr::NoPermission(RuntimeDomain::Method))
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_input() {
+        let encoded = make_trnnut(vec![Module::new("Balances")
+            .block_cooldown(10)
+            .methods(vec![Method::new("transfer").block_cooldown(5)])])
+        .encode();
+
+        assert_eq!(
+            TRNNutRef::new(&[1, 0, 0]),
+            Err(codec::Error::from("expected version : 0"))
+        );
+        assert_eq!(
+            TRNNutRef::new(&encoded[..encoded.len() - 1]),
+            Err(codec::Error::from("Not enough data to fill buffer"))
+        );
+        assert_eq!(
+            TRNNutRef::new(&encoded[..20]),
+            Err(codec::Error::from("expected 32 byte module name"))
+        );
+        let mut bad_name = encoded.clone();
+        bad_name[4] = 0xff;
+        assert_eq!(
+            TRNNutRef::new(&bad_name),
+            Err(codec::Error::from("module names should be utf8 encoded"))
+        );
+        // Only version 1 encodes deny entries, so the flag never reads as a grant
+        let mut denied = encoded.clone();
+        denied[40] |= DENY_MASK;
+        assert_eq!(
+            TRNNutRef::new(&denied),
+            Err(codec::Error::from(
+                "deny entries are only defined for version 1"
+            ))
+        );
+        assert!(TRNNutRef::new(&[encoded.as_slice(), &[0xff]].concat()).is_ok());
+    }
+
+    #[test]
+    fn it_views_constraints_within_limits() {
+        let limits = Limits::default().max_constraints(MAX_CONSTRAINTS * 2);
+        let encoded = make_trnnut(vec![Module::new("Sudo").methods(vec![
+            Method::new("sudo").constraints(vec![0x00; MAX_CONSTRAINTS + 1])
+        ])])
+        .try_encode_limited(limits)
+        .unwrap();
+
+        assert_eq!(
+            TRNNutRef::new(&encoded),
+            Err(codec::Error::from("constraints are too long"))
+        );
+        let trnnut = TRNNutRef::new_limited(&encoded, limits).unwrap();
+        assert_eq!(
+            trnnut.get_method("Sudo", "sudo").unwrap().constraints,
+            Some(&[0x00; MAX_CONSTRAINTS + 1][..])
+        );
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright 2022-2023 Futureverse Corporation Limited
+//!
+//! # TRNNut - V1
+//!
+//! Version 1 TRNNut type.
+//!
+//! Shares the `Module` and `Method` model of version 0 but encodes names as
+//! length prefixed UTF-8 and integers as SCALE compact integers.
+//!
+
+use alloc::{
+    fmt::{self, Display, Formatter},
+    string::String,
+    vec::Vec,
+};
+use codec::{Compact, Decode, Encode, Input, Output};
+use pact::types::{Contract as PactContract, PactType};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    cooldown::CooldownStore,
+    encode::{EncodeError, TryEncode},
+    ensure_canonical,
+    explain::{explain_modules, ValidationTrace},
+    limits::Limits,
+    method::Method,
+    module::Module,
+    trnnut::{
+        find_module, fmt_trnnut, validate_modules, validate_modules_at, MAX_METHODS, MAX_MODULES,
+        MAX_TRNNUT_BYTES,
+    },
+    LimitedDecode, PartialDecode, RuntimeDomain, StrictDecode, ValidationErr,
+};
+
+pub const VERSION_BYTES: [u8; 2] = [1, 0];
+
+const MODULE_BLOCK_COOLDOWN_MASK: u8 = 0x01;
+const MODULE_DENY_MASK: u8 = 0x02;
+const METHOD_BLOCK_COOLDOWN_MASK: u8 = 0x01;
+const METHOD_CONSTRAINTS_MASK: u8 = 0x02;
+const METHOD_DENY_MASK: u8 = 0x04;
+
+/// A TRN permission domain struct for embedding in doughnuts
+/// Unlike version 0, module and method names of any length deserialize
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TRNNutV1 {
+    #[serde(deserialize_with = "deserialize_unbounded_modules")]
+    pub modules: Vec<Module>,
+}
+
+/// A `Module` as deserialized for version 1, whose names are not validated
+#[derive(serde::Deserialize)]
+struct UnboundedModule {
+    name: String,
+    block_cooldown: Option<u32>,
+    methods: Vec<UnboundedMethod>,
+    #[serde(default)]
+    deny: bool,
+}
+
+/// A `Method` as deserialized for version 1, whose name is not validated
+#[derive(serde::Deserialize)]
+struct UnboundedMethod {
+    name: String,
+    block_cooldown: Option<u32>,
+    constraints: Option<Vec<u8>>,
+    #[serde(default)]
+    deny: bool,
+}
+
+fn deserialize_unbounded_modules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Module>, D::Error> {
+    let modules = Vec::<UnboundedModule>::deserialize(deserializer)?;
+    Ok(modules
+        .into_iter()
+        .map(|module| Module {
+            name: module.name,
+            block_cooldown: module.block_cooldown,
+            methods: module
+                .methods
+                .into_iter()
+                .map(|method| Method {
+                    name: method.name,
+                    block_cooldown: method.block_cooldown,
+                    constraints: method.constraints,
+                    deny: method.deny,
+                })
+                .collect(),
+            deny: module.deny,
+        })
+        .collect())
+}
+
+impl TRNNutV1 {
+    /// Returns the module, if it exists in the TRNNut
+    /// Exact names have priority over patterns, then longer pattern prefixes, then suffixes
+    pub fn get_module(&self, module: &str) -> Option<&Module> {
+        find_module(&self.modules, module)
+    }
+
+    /// Validates a runtime module the same way as `TRNNutV0::validate_module`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails with the type of error embedded in `RuntimeDomain`
+    pub fn validate_module(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        validate_modules(&self.modules, module_name, method_name, args)
+    }
+
+    /// Validates a runtime module the same way as `TRNNutV0::validate_module_explain`
+    pub fn validate_module_explain(
+        &self,
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> ValidationTrace {
+        explain_modules(&self.modules, module_name, method_name, args)
+    }
+
+    /// Validates a runtime module the same way as `TRNNutV0::validate_module_at`
+    ///
+    /// # Errors
+    ///
+    /// Will return error if validation fails or a cooldown has not yet elapsed
+    pub fn validate_module_at<S: CooldownStore>(
+        &self,
+        block_number: u32,
+        store: &S,
+        holder: &[u8],
+        module_name: &str,
+        method_name: &str,
+        args: &[PactType],
+    ) -> Result<(), ValidationErr<RuntimeDomain>> {
+        validate_modules_at(
+            &self.modules,
+            block_number,
+            store,
+            holder,
+            module_name,
+            method_name,
+            args,
+        )
+    }
+}
+
+/// Renders as `TRNNutV0` does
+impl Display for TRNNutV1 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_trnnut(f, 1, &self.modules, self.try_encode())
+    }
+}
+
+impl TryEncode for TRNNutV1 {
+    fn try_encode_limited_to<T: Output + ?Sized>(
+        &self,
+        buf: &mut T,
+        limits: Limits,
+    ) -> Result<(), EncodeError> {
+        if self.modules.is_empty() {
+            return Err(EncodeError::NoModules);
+        }
+        if self.modules.len() > MAX_MODULES {
+            return Err(EncodeError::TooManyModules);
+        }
+
+        let mut preliminary_buf = Vec::<u8>::default();
+
+        preliminary_buf.write(&VERSION_BYTES);
+
+        encode_count(self.modules.len(), &mut preliminary_buf);
+        for module in &self.modules {
+            encode_module(module, &mut preliminary_buf, limits)?;
+        }
+
+        // Avoid writing outside of the allocated domain buffer
+        if preliminary_buf.len() > MAX_TRNNUT_BYTES {
+            return Err(EncodeError::PayloadTooLarge {
+                size: preliminary_buf.len(),
+            });
+        }
+        buf.write(preliminary_buf.as_slice());
+
+        Ok(())
+    }
+}
+
+impl Encode for TRNNutV1 {
+    fn encode_to<T: Output + ?Sized>(&self, buf: &mut T) {
+        // Failures are reported by `TryEncode`, nothing is written here
+        let _ = self.try_encode_to(buf);
+    }
+}
+
+impl TRNNutV1 {
+    /// Decode a TRNNut after its version within `limits`
+    pub(crate) fn partial_decode_with<I: Input>(
+        input: &mut I,
+        limits: Limits,
+    ) -> Result<Self, codec::Error> {
+        let module_count = decode_count(input, 1, MAX_MODULES)
+            .map_err(|_| codec::Error::from("invalid module count"))?;
+        let mut modules = Vec::<Module>::default();
+
+        for _ in 0..module_count {
+            modules.push(decode_module(input, limits)?);
+        }
+
+        Ok(Self { modules })
+    }
+}
+
+impl PartialDecode for TRNNutV1 {
+    fn partial_decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::partial_decode_with(input, Limits::default())
+    }
+}
+
+impl Decode for TRNNutV1 {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Self::decode_limited(input, Limits::default())
+    }
+}
+
+impl LimitedDecode for TRNNutV1 {
+    fn decode_limited<I: Input>(input: &mut I, limits: Limits) -> Result<Self, codec::Error> {
+        let version = u16::from_le_bytes([input.read_byte()?, input.read_byte()?]);
+        if version != 1 {
+            return Err(codec::Error::from("expected version : 1"));
+        }
+        Self::partial_decode_with(input, limits)
+    }
+}
+
+impl StrictDecode for TRNNutV1 {
+    fn decode_strict(input: &[u8]) -> Result<Self, codec::Error> {
+        let mut remaining = input;
+        let trnnut = Self::decode(&mut remaining)?;
+        ensure_canonical(trnnut, input, remaining)
+    }
+}
+
+fn encode_count<T: Output + ?Sized>(count: usize, buf: &mut T) {
+    // Counts are bounded by `MAX_MODULES` and `MAX_METHODS` before encoding
+    #[allow(clippy::cast_possible_truncation)]
+    Compact(count as u32).encode_to(buf);
+}
+
+fn decode_count<I: Input>(input: &mut I, min: usize, max: usize) -> Result<usize, codec::Error> {
+    let count = <Compact<u32>>::decode(input)?.0 as usize;
+    if count < min || count > max {
+        return Err(codec::Error::from("count out of range"));
+    }
+    Ok(count)
+}
+
+fn decode_name<I: Input>(input: &mut I, err: &'static str) -> Result<String, codec::Error> {
+    String::from_utf8(Vec::<u8>::decode(input)?).map_err(|_| codec::Error::from(err))
+}
+
+fn encode_module<T: Output + ?Sized>(
+    module: &Module,
+    buf: &mut T,
+    limits: Limits,
+) -> Result<(), EncodeError> {
+    // Denied modules need not list any methods
+    if module.methods.is_empty() && !module.deny {
+        return Err(EncodeError::NoMethods {
+            module: module.name.clone(),
+        });
+    }
+    if module.methods.len() > MAX_METHODS {
+        return Err(EncodeError::TooManyMethods {
+            module: module.name.clone(),
+        });
+    }
+
+    let mut flags = 0;
+    if module.block_cooldown.is_some() {
+        flags |= MODULE_BLOCK_COOLDOWN_MASK;
+    }
+    if module.deny {
+        flags |= MODULE_DENY_MASK;
+    }
+    buf.push_byte(flags);
+    module.name.encode_to(buf);
+    if let Some(cooldown) = module.block_cooldown {
+        Compact(cooldown).encode_to(buf);
+    }
+
+    encode_count(module.methods.len(), buf);
+    for method in &module.methods {
+        encode_method(method, buf, limits)?;
+    }
+
+    Ok(())
+}
+
+fn decode_module<I: Input>(input: &mut I, limits: Limits) -> Result<Module, codec::Error> {
+    let flags = input.read_byte()?;
+    if flags & !(MODULE_BLOCK_COOLDOWN_MASK | MODULE_DENY_MASK) != 0 {
+        return Err(codec::Error::from("invalid module flags"));
+    }
+    let name = decode_name(input, "module names should be utf8 encoded")?;
+    let block_cooldown = if flags & MODULE_BLOCK_COOLDOWN_MASK == MODULE_BLOCK_COOLDOWN_MASK {
+        Some(<Compact<u32>>::decode(input)?.0)
+    } else {
+        None
+    };
+
+    let deny = flags & MODULE_DENY_MASK == MODULE_DENY_MASK;
+    let method_count = decode_count(input, usize::from(!deny), MAX_METHODS)
+        .map_err(|_| codec::Error::from("invalid method count"))?;
+    let mut methods = Vec::<Method>::default();
+    for _ in 0..method_count {
+        methods.push(decode_method(input, limits)?);
+    }
+
+    Ok(Module {
+        name,
+        block_cooldown,
+        methods,
+        deny,
+    })
+}
+
+fn encode_method<T: Output + ?Sized>(
+    method: &Method,
+    buf: &mut T,
+    limits: Limits,
+) -> Result<(), EncodeError> {
+    let constraints = method.constraints.as_ref().filter(|c| !c.is_empty());
+    if constraints.is_some_and(|c| c.len() > limits.max_constraints) {
+        return Err(EncodeError::ConstraintsTooLong);
+    }
+
+    let mut flags = 0;
+    if method.block_cooldown.is_some() {
+        flags |= METHOD_BLOCK_COOLDOWN_MASK;
+    }
+    if constraints.is_some() {
+        flags |= METHOD_CONSTRAINTS_MASK;
+    }
+    if method.deny {
+        flags |= METHOD_DENY_MASK;
+    }
+    buf.push_byte(flags);
+    method.name.encode_to(buf);
+    if let Some(cooldown) = method.block_cooldown {
+        Compact(cooldown).encode_to(buf);
+    }
+    if let Some(constraints) = constraints {
+        constraints.encode_to(buf);
+    }
+
+    Ok(())
+}
+
+fn decode_method<I: Input>(input: &mut I, limits: Limits) -> Result<Method, codec::Error> {
+    let flags = input.read_byte()?;
+    if flags & !(METHOD_BLOCK_COOLDOWN_MASK | METHOD_CONSTRAINTS_MASK | METHOD_DENY_MASK) != 0 {
+        return Err(codec::Error::from("invalid method flags"));
+    }
+    let name = decode_name(input, "method names should be utf8 encoded")?;
+    let block_cooldown = if flags & METHOD_BLOCK_COOLDOWN_MASK == METHOD_BLOCK_COOLDOWN_MASK {
+        Some(<Compact<u32>>::decode(input)?.0)
+    } else {
+        None
+    };
+    let constraints = if flags & METHOD_CONSTRAINTS_MASK == METHOD_CONSTRAINTS_MASK {
+        let length = <Compact<u32>>::decode(input)?.0 as usize;
+        if length > limits.max_constraints {
+            return Err(codec::Error::from("constraints are too long"));
+        }
+        let mut constraints = alloc::vec![0_u8; length];
+        input.read(&mut constraints)?;
+        if constraints.is_empty() || PactContract::decode(&constraints).is_err() {
+            return Err(codec::Error::from("invalid constraints codec"));
+        }
+        Some(constraints)
+    } else {
+        None
+    };
+
+    Ok(Method {
+        name,
+        block_cooldown,
+        constraints,
+        deny: flags & METHOD_DENY_MASK == METHOD_DENY_MASK,
+    })
+}
@@ -12,6 +12,7 @@ use alloc::fmt::{self, Display, Formatter};
 pub enum ValidationErr<Domain: Display> {
     NoPermission(Domain),
     ConstraintsInterpretation,
+    CooldownActive { remaining_blocks: u32 },
 }
 
 impl<Domain: Display> Display for ValidationErr<Domain> {
@@ -23,6 +24,10 @@ impl<Domain: Display> Display for ValidationErr<Domain> {
                 permission_domain
             ),
             Self::ConstraintsInterpretation => write!(f, "error while interpreting constraints"),
+            Self::CooldownActive { remaining_blocks } => write!(
+                f,
+                "TRNNut cooldown is active for another {remaining_blocks} blocks"
+            ),
         }
     }
 }